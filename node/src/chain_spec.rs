@@ -1,7 +1,7 @@
 use sp_core::{Pair, Public, sr25519};
 use node_template_runtime::{
 	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig,
-	SudoConfig, SystemConfig, WASM_BINARY, Signature
+	SudoConfig, SystemConfig, WASM_BINARY, Signature, PoeModuleConfig, KittiesModuleConfig
 };
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_finality_grandpa::AuthorityId as GrandpaId;
@@ -173,5 +173,8 @@ fn testnet_genesis(
                     ..Default::default()
             },
         }),
+		// 新链的存证直接使用最新的存储结构，不需要迁移
+		pallet_poe: Some(PoeModuleConfig::default()),
+		pallet_kitties: Some(KittiesModuleConfig::default()),
 	}
 }
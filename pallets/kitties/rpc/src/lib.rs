@@ -0,0 +1,69 @@
+//! RPC interface for the kitties pallet.
+//! 通过 kitties_checkInvariants 可以在任意区块上检查 kitties 模块的存储是否一致，
+//! 通过 kitties_kittyInfo 可以查询解析之后的猫。
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_kitties_runtime_api::{InvariantViolation, KittyInfo, KittiesApi as KittiesRuntimeApi};
+
+#[rpc]
+pub trait KittiesApi<BlockHash, AccountId, KittyIndex> {
+	/// 检查 kitties 模块的存储是否一致，不传区块哈希时检查最新的区块
+	#[rpc(name = "kitties_checkInvariants")]
+	fn check_invariants(&self, at: Option<BlockHash>) -> Result<Vec<InvariantViolation<AccountId, KittyIndex>>>;
+
+	/// 查询猫的性别、代数、稀有度和外观特征，不传区块哈希时查询最新的区块
+	#[rpc(name = "kitties_kittyInfo")]
+	fn kitty_info(&self, kitty_id: KittyIndex, at: Option<BlockHash>) -> Result<Option<KittyInfo>>;
+}
+
+/// 实现 kitties 模块 RPC 方法的结构，通过 client 调用 runtime 里边的 KittiesApi
+pub struct Kitties<C, B> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Kitties<C, B> {
+	/// 创建 kitties 模块的 RPC 处理器
+	pub fn new(client: Arc<C>) -> Self {
+		Kitties { client, _marker: Default::default() }
+	}
+}
+
+impl<C, Block, AccountId, KittyIndex> KittiesApi<<Block as BlockT>::Hash, AccountId, KittyIndex> for Kitties<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: KittiesRuntimeApi<Block, AccountId, KittyIndex>,
+	AccountId: Codec,
+	KittyIndex: Codec,
+{
+	fn check_invariants(&self, at: Option<<Block as BlockT>::Hash>) -> Result<Vec<InvariantViolation<AccountId, KittyIndex>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.check_invariants(&at).map_err(|e| RpcError {
+			code: ErrorCode::ServerError(1),
+			message: "Unable to check kitties invariants.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})
+	}
+
+	fn kitty_info(&self, kitty_id: KittyIndex, at: Option<<Block as BlockT>::Hash>) -> Result<Option<KittyInfo>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.kitty_info(&at, kitty_id).map_err(|e| RpcError {
+			code: ErrorCode::ServerError(1),
+			message: "Unable to query kitty info.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})
+	}
+}
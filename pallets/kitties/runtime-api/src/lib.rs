@@ -0,0 +1,20 @@
+//! Runtime API definition for the kitties pallet.
+//! 链下（例如监控服务、前端）可以通过这些接口检查 kitties 模块的存储是否一致，以及查询解析之后的猫。
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+pub use pallet_kitties::{Gender, InvariantViolation, KittyInfo, KittyTraits, Rarity};
+
+sp_api::decl_runtime_apis! {
+	pub trait KittiesApi<AccountId, KittyIndex> where
+		AccountId: Codec,
+		KittyIndex: Codec,
+	{
+		/// 检查 KittyOwners 和 OwnedKitties 是否一致，以及质押是否足够，全部一致时返回空列表
+		fn check_invariants() -> Vec<InvariantViolation<AccountId, KittyIndex>>;
+		/// 查询猫的性别、代数、稀有度和外观特征，猫不存在或者已经释放返回 None
+		fn kitty_info(kitty_id: KittyIndex) -> Option<KittyInfo>;
+	}
+}
@@ -0,0 +1,115 @@
+//! Kitty 的基因：从 DNA 的字节里边解析出性别、稀有度和外观特征，以及繁殖的时候怎么遗传
+//!
+//! DNA 一共 16 个字节：
+//! - 第 0 个字节：性别，偶数是公猫，奇数是母猫
+//! - 第 1 个字节：稀有度，越大越稀有
+//! - 第 2 ~ 7 个字节：六个外观特征，每个特征有 TRAIT_VARIANTS 种
+//! - 第 8 ~ 15 个字节：隐性基因，不影响外观，只参与遗传
+//!
+//! 代数没办法从随机的 DNA 里边得到，单独保存在 KittyGenerations 里边
+
+use codec::{Encode, Decode};
+use frame_support::RuntimeDebug;
+use super::Kitty;
+
+pub const DNA_LEN: usize = 16;
+const GENDER_GENE: usize = 0;
+const RARITY_GENE: usize = 1;
+const TRAIT_GENES: usize = 2;
+// 每个外观特征有多少种
+pub const TRAIT_VARIANTS: u8 = 16;
+// 每个基因突变的概率是 MUTATION_THRESHOLD / 256
+const MUTATION_THRESHOLD: u8 = 8;
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub enum Gender {
+	Male,
+	Female,
+}
+
+// 稀有度：普通（75%）、稀有（约 19%）、史诗（约 6%）、传说（1/256）
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rarity {
+	Common,
+	Rare,
+	Epic,
+	Legendary,
+}
+
+// 外观特征，每个值都在 0 ~ TRAIT_VARIANTS - 1 之间
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct KittyTraits {
+	pub fur_colour: u8,
+	pub pattern: u8,
+	pub eye_colour: u8,
+	pub eye_shape: u8,
+	pub mouth: u8,
+	pub tail: u8,
+}
+
+// 解析之后的 Kitty，给查询接口用
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct KittyInfo {
+	pub dna: [u8; DNA_LEN],
+	pub gender: Gender,
+	pub generation: u32,
+	pub rarity: Rarity,
+	pub traits: KittyTraits,
+}
+
+impl Kitty {
+	pub fn gender(&self) -> Gender {
+		if self.0[GENDER_GENE] % 2 == 0 { Gender::Male } else { Gender::Female }
+	}
+
+	pub fn rarity(&self) -> Rarity {
+		match self.0[RARITY_GENE] {
+			0..=191 => Rarity::Common,
+			192..=239 => Rarity::Rare,
+			240..=254 => Rarity::Epic,
+			_ => Rarity::Legendary,
+		}
+	}
+
+	pub fn traits(&self) -> KittyTraits {
+		let trait_of = |i: usize| self.0[TRAIT_GENES + i] % TRAIT_VARIANTS;
+		KittyTraits {
+			fur_colour: trait_of(0),
+			pattern: trait_of(1),
+			eye_colour: trait_of(2),
+			eye_shape: trait_of(3),
+			mouth: trait_of(4),
+			tail: trait_of(5),
+		}
+	}
+
+	pub fn info(&self, generation: u32) -> KittyInfo {
+		KittyInfo {
+			dna: self.0,
+			gender: self.gender(),
+			generation,
+			rarity: self.rarity(),
+			traits: self.traits(),
+		}
+	}
+}
+
+// 繁殖：性别由随机数决定，其他每个基因随机继承父母中的一个，并且有小概率突变
+pub fn breed_dna(dna1: &[u8; DNA_LEN], dna2: &[u8; DNA_LEN], selector: &[u8; DNA_LEN]) -> [u8; DNA_LEN] {
+	let mut new_dna = [0u8; DNA_LEN];
+	new_dna[GENDER_GENE] = selector[GENDER_GENE];
+
+	for i in RARITY_GENE..DNA_LEN {
+		let gene = if selector[i] & 1 == 0 { dna1[i] } else { dna2[i] };
+		new_dna[i] = if selector[i] < MUTATION_THRESHOLD {
+			gene ^ selector[(i + DNA_LEN / 2) % DNA_LEN]
+		} else {
+			gene
+		};
+	}
+	new_dna
+}
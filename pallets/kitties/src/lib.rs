@@ -1,13 +1,19 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Encode, Decode};
-use frame_support::{decl_module,decl_storage, decl_event, decl_error, StorageValue, ensure, StorageMap, traits::Randomness, Parameter,traits::{ExistenceRequirement ,Get, Currency, ReservableCurrency}
+use frame_support::{decl_module,decl_storage, decl_event, decl_error, StorageValue, ensure, transactional, StorageMap, traits::Randomness, Parameter,traits::{ExistenceRequirement ,Get, Currency, ReservableCurrency, BalanceStatus, EnsureOrigin},
+	RuntimeDebug, weights::Weight, storage::{with_transaction, TransactionOutcome},
 };
 use sp_io::hashing::blake2_128;
 use frame_system::ensure_signed;
-use sp_runtime::{DispatchError,traits::{AtLeast32Bit,Bounded}};
+use sp_runtime::{DispatchError,DispatchResult,traits::{AtLeast32Bit,Bounded,One,Saturating,Zero}};
+use sp_std::{prelude::*, collections::btree_map::BTreeMap};
 
 mod benchmarking;
+pub mod genetics;
+pub mod migrations;
+
+pub use genetics::{Gender, KittyInfo, KittyTraits, Rarity};
 
 #[cfg(test)]
 mod mock;
@@ -15,11 +21,76 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
-// 定义一个 kitty 的数据结构
+// 存储结构的版本，修改存储结构的时候增加一个版本，并在 on_runtime_upgrade 里边迁移旧的数据
+// V1_0_0：buy 没有更新 OwnedKitties，拥有者索引可能和 KittyOwners 不一致
+// V2_0_0：按照 KittyOwners 重建了 OwnedKitties
+// V3_0_0：KittyDeposits 记录每只猫实际质押的押金和质押的账号
+// V4_0_0：KittyGenerations 记录每只猫的代数
+// V5_0_0：OfferCount 记录每只猫的报价数量
+// V6_0_0：KittyLitters 记录每只猫的繁殖次数
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Releases {
+	V1_0_0,
+	V2_0_0,
+	V3_0_0,
+	V4_0_0,
+	V5_0_0,
+	V6_0_0,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1_0_0
+	}
+}
+
+// 存储不一致的情况，由 check_invariants 返回，测试和 RPC 都可以用
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub enum InvariantViolation<AccountId, KittyIndex> {
+	// KittyOwners 里边记录了拥有者，但是 OwnedKitties 里边没有这只猫
+	MissingOwnedEntry(AccountId, KittyIndex),
+	// OwnedKitties 里边有这只猫，但是 KittyOwners 里边的拥有者不是这个账号
+	StaleOwnedEntry(AccountId, KittyIndex),
+	// 账号质押的金额不够猫的押金、拍卖出价和报价
+	InsufficientReserve(AccountId),
+	// 猫没有押金记录，或者记录的质押账号不是猫的拥有者
+	DepositMismatch(AccountId, KittyIndex),
+}
+
+// 定义一个 kitty 的数据结构，DNA 的含义见 genetics.rs
 #[derive(Encode, Decode)]
 pub struct Kitty(pub [u8; 16]);
 
+// 拍卖的出价：出价人、出价，以及出价的时候一起质押的猫的押金，成交之后这部分押金就是猫的押金
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Bid<AccountId, Balance> {
+	pub bidder: AccountId,
+	pub amount: Balance,
+	pub deposit: Balance,
+}
+
+// 拍卖的信息：卖家、保留价（第一个出价不能低于这个价格）、每次加价的最小幅度、结束的区块、当前最高的出价
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Auction<AccountId, Balance, BlockNumber> {
+	pub seller: AccountId,
+	pub reserve_price: Balance,
+	pub min_increment: Balance,
+	pub end: BlockNumber,
+	pub highest_bid: Option<Bid<AccountId, Balance>>,
+}
+
+// 买家的报价：出价、猫的押金和过期的区块，出价和猫的押金在报价的时候一起质押
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Offer<Balance, BlockNumber> {
+	pub amount: Balance,
+	pub deposit: Balance,
+	pub expires_at: BlockNumber,
+}
+
 type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+type AuctionOf<T> = Auction<<T as frame_system::Config>::AccountId, BalanceOf<T>, <T as frame_system::Config>::BlockNumber>;
+type OfferOf<T> = Offer<BalanceOf<T>, <T as frame_system::Config>::BlockNumber>;
 
 pub trait Config: frame_system::Config {
     // 如果有触发事件，就必须包含这一行
@@ -38,6 +109,20 @@ pub trait Config: frame_system::Config {
 	type NewKittyReserve: Get<BalanceOf<Self>>;
 	// Currency 类型，用于质押等于资产相关的操作
 	type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
+	// 拍卖最长可以持续多少个区块
+	type MaxAuctionDuration: Get<Self::BlockNumber>;
+	// 同一个区块最多能有多少个拍卖结束，用来限制 on_finalize 里边结算的工作量
+	type MaxAuctionsPerBlock: Get<u32>;
+	// 繁殖之后父母需要休息的基础区块数，实际的休息时间是 基础区块数 × (1 + 代数 + 已经繁殖的次数)
+	type BaseBreedingCooldown: Get<Self::BlockNumber>;
+	// 每只猫最多可以繁殖多少次
+	type MaxLitters: Get<u32>;
+	// 每只猫最多可以有多少个报价，用来限制 release 退还报价的工作量
+	type MaxOffersPerKitty: Get<u32>;
+	// 报价最长可以持续多少个区块，过期的报价任何人都可以清理
+	type MaxOfferDuration: Get<Self::BlockNumber>;
+	// 可以调用 rebase_deposits，按照新的 NewKittyReserve 调整已有的猫的押金，一般是 Root 或者议会
+	type DepositOrigin: EnsureOrigin<Self::Origin>;
 }
 
 // 定义数据存储
@@ -45,6 +130,8 @@ decl_storage! {
 	// 定义所储存的数据是属于 KittiesModule 的，( 这个需要和 runtime > lib.rss > construct_runtime 部分引用这个 pallet 的名称对应？）
 	// T: Config 里边的 Config 就是第17行定义的 Config
 	trait Store for Module<T: Config> as KittiesModule {
+		// 存储结构的版本，新链直接从最新的版本开始
+		StorageVersion build(|_: &GenesisConfig| Releases::V6_0_0): Releases;
 		// 保存所有 kitty 的数据，用 KittyIndex 作为健值
 		pub Kitties get(fn kitties): map hasher(blake2_128_concat) T::KittyIndex => Option<Kitty>;
 		// 保存 kitty 的总数，严格上来说，应该是最大的 Kitty 的健值索引，因为如果支持 kitty 的删除，实现上就不对了。
@@ -54,6 +141,14 @@ decl_storage! {
 		pub KittyOwners get(fn kitty_owners): map hasher(blake2_128_concat) T::KittyIndex => Option<T::AccountId>;
 		// 记录某个拥有者与猫之间的关系
 		pub OwnedKitties get(fn owned_kitties):double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) T::KittyIndex => Option<T::KittyIndex>;
+		// 每只猫的代数，创建的猫是第 0 代，繁殖出来的猫比父母中代数大的那只多一代
+		pub KittyGenerations get(fn kitty_generations): map hasher(blake2_128_concat) T::KittyIndex => u32;
+		// 每只猫已经繁殖的次数
+		pub KittyLitters get(fn kitty_litters): map hasher(blake2_128_concat) T::KittyIndex => u32;
+		// 每只猫可以再次繁殖的区块，在这个区块之前繁殖会返回 KittyTired
+		pub KittyCooldowns get(fn kitty_cooldowns): map hasher(blake2_128_concat) T::KittyIndex => T::BlockNumber;
+		// 已经释放的猫和释放的区块，KittyParents、KittyChildren、KittyPartners 里边的记录保留下来作为历史记录
+		pub DeadKitties get(fn dead_kitties): map hasher(blake2_128_concat) T::KittyIndex => Option<T::BlockNumber>;
 		// 记录某只猫的父母，因为猫可能没有父母，所以用 Option
 		pub KittyParents get(fn kitty_parents):map hasher(blake2_128_concat) T::KittyIndex => Option<(T::KittyIndex, T::KittyIndex)>;
 		// 记录某只猫的孩子们，第一个值是主猫，第二个是孩子，值也是孩子
@@ -61,17 +156,49 @@ decl_storage! {
 		// 记录某只猫的伴侣，第一个是主猫，第二个是伴侣猫，值是伴侣猫
 		pub KittyPartners get(fn kitty_partners):double_map hasher(blake2_128_concat) T::KittyIndex, hasher(blake2_128_concat) T::KittyIndex => Option<T::KittyIndex>;
 
+		// 每只猫实际质押的押金：质押的账号和数量，NewKittyReserve 修改之后已有的猫仍然按照这里的记录解质押
+		pub KittyDeposits get(fn kitty_deposits): map hasher(blake2_128_concat) T::KittyIndex => Option<(T::AccountId, BalanceOf<T>)>;
+
+		// 等待接收的赠送，值是接收人，接收人确认之后才会质押接收人的押金
+		pub PendingGifts get(fn pending_gifts): map hasher(blake2_128_concat) T::KittyIndex => Option<T::AccountId>;
+
 		pub KittyPrices get(fn kitty_prices): map hasher(blake2_128_concat) T::KittyIndex => Option<BalanceOf<T>>;
+		// 正在拍卖的猫
+		pub Auctions get(fn auctions): map hasher(blake2_128_concat) T::KittyIndex => Option<AuctionOf<T>>;
+		// 每个区块结束的拍卖，在 on_finalize 里边结算
+		pub AuctionEnds get(fn auction_ends): map hasher(twox_64_concat) T::BlockNumber => Vec<T::KittyIndex>;
+		// 买家对猫的报价，第一个键是猫，第二个键是报价的人，没有挂单的猫也可以报价
+		pub Offers get(fn offers): double_map hasher(blake2_128_concat) T::KittyIndex, hasher(blake2_128_concat) T::AccountId => Option<OfferOf<T>>;
+		// 每只猫的报价数量，不超过 MaxOffersPerKitty
+		pub OfferCount get(fn offer_count): map hasher(blake2_128_concat) T::KittyIndex => u32;
 	}
 }
 
 // 定义事件
 decl_event!(
 	// where 后边的部分，是表示在 Event 里边需要用的一些类型来自哪个 Config 定义
-	pub enum Event<T> where AccountId = <T as frame_system::Config>::AccountId, KittyIndex = <T as Config>::KittyIndex, BalanceOf = BalanceOf<T> {
+	pub enum Event<T> where AccountId = <T as frame_system::Config>::AccountId, KittyIndex = <T as Config>::KittyIndex, BalanceOf = BalanceOf<T>, BlockNumber = <T as frame_system::Config>::BlockNumber {
 		Created(AccountId, KittyIndex),
 		Transferred(AccountId, AccountId, KittyIndex),
+		// 赠送的各个阶段：发起（赠送人、接收人、猫）、取消（赠送人、接收人、猫），接收之后触发 Transferred
+		GiftOffered(AccountId, AccountId, KittyIndex),
+		GiftCancelled(AccountId, AccountId, KittyIndex),
 		KittyAsk(AccountId, KittyIndex, Option<BalanceOf>),
+		// 拍卖的各个阶段：开始（卖家、猫、保留价、结束区块）、出价、被超过（退还出价）、取消、成交（卖家、买家、猫、成交价）、流拍
+		AuctionCreated(AccountId, KittyIndex, BalanceOf, BlockNumber),
+		BidPlaced(AccountId, KittyIndex, BalanceOf),
+		Outbid(AccountId, KittyIndex, BalanceOf),
+		AuctionCancelled(AccountId, KittyIndex),
+		AuctionSettled(AccountId, AccountId, KittyIndex, BalanceOf),
+		AuctionUnsold(AccountId, KittyIndex),
+		// 报价（报价人、猫、出价、过期区块）、撤回报价、接受报价（卖家、买家、猫、成交价）
+		OfferMade(AccountId, KittyIndex, BalanceOf, BlockNumber),
+		OfferWithdrawn(AccountId, KittyIndex),
+		OfferAccepted(AccountId, AccountId, KittyIndex, BalanceOf),
+		// 释放了猫（拥有者、猫、退还的押金）
+		Released(AccountId, KittyIndex, BalanceOf),
+		// 调整了猫的押金（猫、质押的账号、之前的押金、新的押金）
+		DepositRebased(KittyIndex, AccountId, BalanceOf, BalanceOf),
 	}
 );
 
@@ -88,6 +215,44 @@ decl_error! {
 		AlreadyOwned,
 		NotForSale,
 		PriceTooLow,
+		// 猫正在拍卖，不能转让、挂单或者购买
+		KittyOnAuction,
+		// 猫没有在拍卖
+		AuctionNotExists,
+		// 结束区块必须在当前区块之后，并且不能超过最长拍卖时间
+		InvalidAuctionEnd,
+		// 这个区块结束的拍卖已经太多了
+		TooManyAuctions,
+		// 拍卖已经结束
+		AuctionEnded,
+		// 出价低于保留价，或者加价低于最小幅度
+		BidTooLow,
+		// 不能对自己的猫出价
+		BidOnOwnKitty,
+		// 已经有人出价的拍卖不能取消
+		AuctionHasBids,
+		// 过期区块必须在当前区块之后
+		InvalidOfferExpiry,
+		// 没有这个报价
+		OfferNotExists,
+		// 报价已经过期
+		OfferExpired,
+		// 报价还没有过期，只有报价的人可以撤回
+		OfferNotExpired,
+		// 繁殖需要一只公猫和一只母猫
+		RequiredMaleAndFemale,
+		// 父母还在休息，不能繁殖
+		KittyTired,
+		// 父母已经达到最多的繁殖次数
+		TooManyLitters,
+		// 这只猫没有等待接收的赠送
+		GiftNotExists,
+		// 不是赠送的接收人
+		NotGiftRecipient,
+		// 报价不能为 0
+		ZeroOffer,
+		// 这只猫的报价已经太多了
+		TooManyOffers,
 	}
 }
 
@@ -99,6 +264,31 @@ decl_module! {
 		// 如果有触发事件，必须包含这一行
 		fn deposit_event() = default;
 
+		fn on_runtime_upgrade() -> Weight {
+			migrations::migrate_to_v2::<T>()
+				.saturating_add(migrations::migrate_to_v3::<T>())
+				.saturating_add(migrations::migrate_to_v4::<T>())
+				.saturating_add(migrations::migrate_to_v5::<T>())
+				.saturating_add(migrations::migrate_to_v6::<T>())
+		}
+
+		// 这个区块结束的拍卖会在 on_finalize 里边结算，结算用的权重在 on_initialize 里边先算好
+		// 每个拍卖读 5 次：拍卖、押金记录、买家、卖家和之前质押押金的账户
+		// 写 11 次：拍卖、转出价的两个账户、押金记录的删除和写入、释放押金的账户、拥有者、拥有者索引两次、挂单、赠送
+		fn on_initialize(n: T::BlockNumber) -> Weight {
+			let ending = Self::auction_ends(n).len() as Weight;
+			T::DbWeight::get().reads_writes(1 + ending * 5, 1 + ending * 11)
+		}
+
+		// 结算这个区块结束的拍卖
+		fn on_finalize(n: T::BlockNumber) {
+			for kitty_id in <AuctionEnds::<T>>::take(n) {
+				if let Some(auction) = <Auctions::<T>>::take(kitty_id) {
+					Self::settle_auction(kitty_id, auction);
+				}
+			}
+		}
+
 		#[weight = T::DbWeight::get().writes(5) + T::DbWeight::get().reads(4) + 139_300_000]
 		pub fn create(origin){
 			// 加 “?” 只提取正确时候返回的数据
@@ -107,7 +297,7 @@ decl_module! {
 
 			Self::deposit_event(RawEvent::Created(sender, kitty_id));
 		}
-		#[weight = 0]
+		#[weight = T::DbWeight::get().reads_writes(2, 1) + 25_000_000]
 		pub fn transfer(origin, to: T::AccountId, kitty_id: T::KittyIndex){
 			let sender = ensure_signed(origin)?;
 			// 判断 KittyIndex 是否存在，通过 ok_or 将错误抛出来，如果没有将返回一个 option 类型的数据
@@ -117,24 +307,48 @@ decl_module! {
 
 			// 不能转让给自己
 			ensure!(to != sender, Error::<T>::TransferToSelf);
+			// 正在拍卖的猫不能转让
+			ensure!(!<Auctions::<T>>::contains_key(kitty_id), Error::<T>::KittyOnAuction);
+
+			// 转让只是发起赠送，接收人确认之前不会质押接收人的代币，再次转让会替换之前的接收人
+			<PendingGifts::<T>>::insert(kitty_id, &to);
 
-			// 质押被转让人的代币
-			T::Currency::reserve(&to, T::NewKittyReserve::get()).map_err(|_| Error::<T>::MoneyNotEnough )?;
+			// 触发赠送的事件
+			Self::deposit_event(RawEvent::GiftOffered(sender, to, kitty_id));
+		}
+		// 接收人确认接收赠送，这时才质押接收人的押金，并且释放赠送人的押金
+		#[weight = T::DbWeight::get().reads_writes(6, 9) + 60_000_000]
+		pub fn accept_gift(origin, kitty_id: T::KittyIndex){
+			let sender = ensure_signed(origin)?;
+			let recipient = Self::pending_gifts(kitty_id).ok_or( Error::<T>::GiftNotExists )?;
+			ensure!(recipient == sender, Error::<T>::NotGiftRecipient);
+			let owner = Self::kitty_owners(kitty_id).ok_or( Error::<T>::KittyNotExists )?;
+			ensure!(!<Auctions::<T>>::contains_key(kitty_id), Error::<T>::KittyOnAuction);
 
-			// 解质押转出人的代币
-			// 如果配置的质押代币数量变化了，可能这里会出问题。其实最好的方式是每个 kitty 都记录下，它当时质押的代币数量
-			T::Currency::unreserve(&sender, T::NewKittyReserve::get());
+			// 质押接收人的代币
+			let deposit = T::NewKittyReserve::get();
+			T::Currency::reserve(&sender, deposit).map_err(|_| Error::<T>::MoneyNotEnough )?;
 
-			// 修改 KITTY 的拥有人
-			KittyOwners::<T>::insert(kitty_id, &to);
-			// 从之前的拥有人中删除关系
-			OwnedKitties::<T>::remove(&sender, kitty_id);
-			OwnedKitties::<T>::insert(&to, kitty_id, kitty_id);
+			// 修改 KITTY 的拥有人，同时按照记录解质押转出人的代币
+			Self::transfer_ownership(&owner, &sender, kitty_id, deposit);
 
 			// 触发转让的事件
-			Self::deposit_event(RawEvent::Transferred(sender, to, kitty_id));
+			Self::deposit_event(RawEvent::Transferred(owner, sender, kitty_id));
 		}
-		#[weight = T::DbWeight::get().writes(10) + T::DbWeight::get().reads(8) + 233_100_000]
+		// 赠送人撤回赠送，或者接收人拒绝接收
+		#[weight = T::DbWeight::get().reads_writes(2, 1) + 20_000_000]
+		pub fn cancel_gift(origin, kitty_id: T::KittyIndex){
+			let sender = ensure_signed(origin)?;
+			let recipient = Self::pending_gifts(kitty_id).ok_or( Error::<T>::GiftNotExists )?;
+			let owner = Self::kitty_owners(kitty_id).ok_or( Error::<T>::KittyNotExists )?;
+			ensure!(sender == owner || sender == recipient, Error::<T>::NotKittyOwner);
+
+			<PendingGifts::<T>>::remove(kitty_id);
+
+			Self::deposit_event(RawEvent::GiftCancelled(owner, recipient, kitty_id));
+		}
+		// 除了新猫的记录，还要读写父母双方的休息时间、繁殖次数和代数，以及新猫的代数
+		#[weight = T::DbWeight::get().writes(16) + T::DbWeight::get().reads(14) + 233_100_000]
 		pub fn breed(origin, kitty_id1: T::KittyIndex, kitty_id2: T::KittyIndex){
 			let sender = ensure_signed(origin)?;
 			let new_kitty_id = Self::do_breed(&sender, kitty_id1, kitty_id2)?;
@@ -146,6 +360,8 @@ decl_module! {
 			let sender = ensure_signed(origin)?;
 			// 判定是不是 kitty 的主人
 			ensure!( Some( sender.clone() ) == Self::kitty_owners(kitty_id), Error::<T>::NotKittyOwner);
+			// 正在拍卖的猫不能挂单
+			ensure!(!<Auctions::<T>>::contains_key(kitty_id), Error::<T>::KittyOnAuction);
 			
 			// mutate_exists ：修改 map 指定键的值，如果为 none 就删除，第二个参数是一个闭包，提供的参数是键值 
 			<KittyPrices<T>>::mutate_exists(kitty_id, |price| *price = new_price);
@@ -153,39 +369,332 @@ decl_module! {
 			// 触发一个挂单的事件
 			Self::deposit_event(RawEvent::KittyAsk(sender, kitty_id, new_price));
 		}
+		// 转账失败的时候要回滚前边的质押，所以整个调用放在一个存储事务里边
 		#[weight = 0]
-		pub fn buy(origin, kitty_id: T::KittyIndex, price: BalanceOf<T>){
+		#[transactional]
+		pub fn buy(origin, kitty_id: T::KittyIndex, price: BalanceOf<T>) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 			// 检查是否存在，顺便提取出售者
 			let owner = Self::kitty_owners(kitty_id).ok_or( Error::<T>::KittyNotExists )?;
 			// 已经是自己的不再折腾
 			ensure!( sender.clone() != owner, Error::<T>::AlreadyOwned);
 			let kitty_price = Self::kitty_prices(kitty_id).ok_or( Error::<T>::NotForSale)?;
+			ensure!(!<Auctions::<T>>::contains_key(kitty_id), Error::<T>::KittyOnAuction);
 			// 确认出价是不是太低
 			ensure!( kitty_price <= price, Error::<T>::PriceTooLow);
 
 			// 转质押 + 扣款
 			// 对于购买者，先质押购买的和创建抵押的
-			T::Currency::reserve(&sender, T::NewKittyReserve::get() + kitty_price ).map_err(|_| Error::<T>::MoneyNotEnough )?;
-			// 释放卖出者之前质押的
-			T::Currency::unreserve(&owner, T::NewKittyReserve::get());
+			let deposit = T::NewKittyReserve::get();
+			T::Currency::reserve(&sender, deposit + kitty_price ).map_err(|_| Error::<T>::MoneyNotEnough )?;
 			// 释放购买者需要支付用来质押的
 			T::Currency::unreserve(&sender, kitty_price);
 			// 转账
 			T::Currency::transfer(&sender, &owner, kitty_price, ExistenceRequirement::KeepAlive)?;
 
-			// 移除价格挂单
-			<KittyPrices::<T>>::remove(&kitty_id);
-			// 转移 Kitty
-			<KittyOwners::<T>>::insert(&kitty_id, sender.clone() );
+			// 转移 Kitty，同时移除价格挂单，释放卖出者之前质押的
+			Self::transfer_ownership(&owner, &sender, kitty_id, deposit);
 
 			// 触发所有权转让的事件
 			Self::deposit_event(RawEvent::Transferred(owner, sender, kitty_id));
+			Ok(())
+		}
+		// 发起拍卖：保留价、最小加价幅度、结束的区块，之前的挂单会被取消
+		#[weight = T::DbWeight::get().reads_writes(3, 3) + 45_000_000]
+		pub fn create_auction(origin, kitty_id: T::KittyIndex, reserve_price: BalanceOf<T>, min_increment: BalanceOf<T>, end: T::BlockNumber){
+			let sender = ensure_signed(origin)?;
+			let owner = Self::kitty_owners(kitty_id).ok_or( Error::<T>::KittyNotExists )?;
+			ensure!(owner == sender, Error::<T>::NotKittyOwner);
+			ensure!(!<Auctions::<T>>::contains_key(kitty_id), Error::<T>::KittyOnAuction);
+			// 结束区块必须在当前区块之后，并且不能超过最长拍卖时间
+			let now = <frame_system::Module<T>>::block_number();
+			ensure!(end > now && end <= now.saturating_add(T::MaxAuctionDuration::get()), Error::<T>::InvalidAuctionEnd);
+			ensure!((Self::auction_ends(end).len() as u32) < T::MaxAuctionsPerBlock::get(), Error::<T>::TooManyAuctions);
+
+			<KittyPrices::<T>>::remove(kitty_id);
+			<Auctions::<T>>::insert(kitty_id, Auction {
+				seller: sender.clone(),
+				reserve_price,
+				min_increment,
+				end,
+				highest_bid: None,
+			});
+			<AuctionEnds::<T>>::append(end, kitty_id);
+
+			Self::deposit_event(RawEvent::AuctionCreated(sender, kitty_id, reserve_price, end));
+		}
+		// 出价：质押出价和猫的押金，之前的最高出价人的质押全部退还
+		#[weight = T::DbWeight::get().reads_writes(3, 3) + 60_000_000]
+		pub fn bid(origin, kitty_id: T::KittyIndex, amount: BalanceOf<T>){
+			let sender = ensure_signed(origin)?;
+			let mut auction = Self::auctions(kitty_id).ok_or( Error::<T>::AuctionNotExists )?;
+			ensure!(<frame_system::Module<T>>::block_number() < auction.end, Error::<T>::AuctionEnded);
+			ensure!(sender != auction.seller, Error::<T>::BidOnOwnKitty);
+			// 第一个出价不能低于保留价，之后的出价至少要比当前最高出价多最小加价幅度
+			// 最小加价幅度为 0 的时候，出价也必须比当前最高出价高，相同的出价不能替换领先的人
+			match &auction.highest_bid {
+				Some(highest) => ensure!(
+					amount > highest.amount && amount >= highest.amount.saturating_add(auction.min_increment),
+					Error::<T>::BidTooLow
+				),
+				None => ensure!(amount >= auction.reserve_price, Error::<T>::BidTooLow),
+			}
+
+			// 成交之后买家需要质押猫的押金，所以出价的时候一起质押，保证结算的时候不会失败
+			let deposit = T::NewKittyReserve::get();
+			T::Currency::reserve(&sender, amount.saturating_add(deposit)).map_err(|_| Error::<T>::MoneyNotEnough )?;
+			if let Some(previous) = auction.highest_bid.take() {
+				T::Currency::unreserve(&previous.bidder, previous.amount.saturating_add(previous.deposit));
+				Self::deposit_event(RawEvent::Outbid(previous.bidder, kitty_id, previous.amount));
+			}
+			auction.highest_bid = Some(Bid { bidder: sender.clone(), amount, deposit });
+			<Auctions::<T>>::insert(kitty_id, auction);
+
+			Self::deposit_event(RawEvent::BidPlaced(sender, kitty_id, amount));
+		}
+		// 卖家取消还没有人出价的拍卖
+		#[weight = T::DbWeight::get().reads_writes(2, 2) + 30_000_000]
+		pub fn cancel_auction(origin, kitty_id: T::KittyIndex){
+			let sender = ensure_signed(origin)?;
+			let auction = Self::auctions(kitty_id).ok_or( Error::<T>::AuctionNotExists )?;
+			ensure!(auction.seller == sender, Error::<T>::NotKittyOwner);
+			ensure!(auction.highest_bid.is_none(), Error::<T>::AuctionHasBids);
+
+			<Auctions::<T>>::remove(kitty_id);
+			<AuctionEnds::<T>>::mutate(auction.end, |ids| ids.retain(|id| *id != kitty_id));
+
+			Self::deposit_event(RawEvent::AuctionCancelled(sender, kitty_id));
+		}
+		// 对任意一只猫报价，质押出价和猫的押金，重复报价会替换之前的报价
+		#[weight = T::DbWeight::get().reads_writes(4, 3) + 55_000_000]
+		pub fn make_offer(origin, kitty_id: T::KittyIndex, amount: BalanceOf<T>, expires_at: T::BlockNumber){
+			let sender = ensure_signed(origin)?;
+			let owner = Self::kitty_owners(kitty_id).ok_or( Error::<T>::KittyNotExists )?;
+			ensure!(sender != owner, Error::<T>::AlreadyOwned);
+			ensure!(!amount.is_zero(), Error::<T>::ZeroOffer);
+			// 过期区块必须在当前区块之后，并且不能超过最长报价时间
+			let now = <frame_system::Module<T>>::block_number();
+			ensure!(expires_at > now && expires_at <= now.saturating_add(T::MaxOfferDuration::get()), Error::<T>::InvalidOfferExpiry);
+			let previous = <Offers::<T>>::get(kitty_id, &sender);
+			// 替换自己之前的报价不增加报价的数量
+			if previous.is_none() {
+				ensure!(Self::offer_count(kitty_id) < T::MaxOffersPerKitty::get(), Error::<T>::TooManyOffers);
+			}
+
+			// 先质押新的报价，成功之后再释放之前的报价
+			let deposit = T::NewKittyReserve::get();
+			T::Currency::reserve(&sender, amount.saturating_add(deposit)).map_err(|_| Error::<T>::MoneyNotEnough )?;
+			match previous {
+				Some(previous) => {
+					T::Currency::unreserve(&sender, previous.amount.saturating_add(previous.deposit));
+				}
+				None => <OfferCount::<T>>::mutate(kitty_id, |count| *count = count.saturating_add(1)),
+			}
+			<Offers::<T>>::insert(kitty_id, &sender, Offer { amount, deposit, expires_at });
+
+			Self::deposit_event(RawEvent::OfferMade(sender, kitty_id, amount, expires_at));
+		}
+		// 撤回报价，释放质押，过期的报价也通过这个方法释放
+		#[weight = T::DbWeight::get().reads_writes(2, 3) + 35_000_000]
+		pub fn withdraw_offer(origin, kitty_id: T::KittyIndex){
+			let sender = ensure_signed(origin)?;
+			let offer = <Offers::<T>>::take(kitty_id, &sender).ok_or( Error::<T>::OfferNotExists )?;
+			Self::decrease_offer_count(kitty_id);
+			T::Currency::unreserve(&sender, offer.amount.saturating_add(offer.deposit));
+
+			Self::deposit_event(RawEvent::OfferWithdrawn(sender, kitty_id));
+		}
+		// 任何人都可以清理过期的报价，质押退还给报价的人，过期的报价不会一直占着报价的数量
+		#[weight = T::DbWeight::get().reads_writes(2, 3) + 35_000_000]
+		pub fn clear_expired_offer(origin, kitty_id: T::KittyIndex, bidder: T::AccountId){
+			ensure_signed(origin)?;
+			let offer = Self::offers(kitty_id, &bidder).ok_or( Error::<T>::OfferNotExists )?;
+			ensure!(<frame_system::Module<T>>::block_number() >= offer.expires_at, Error::<T>::OfferNotExpired);
+
+			<Offers::<T>>::remove(kitty_id, &bidder);
+			Self::decrease_offer_count(kitty_id);
+			T::Currency::unreserve(&bidder, offer.amount.saturating_add(offer.deposit));
+
+			Self::deposit_event(RawEvent::OfferWithdrawn(bidder, kitty_id));
+		}
+		// 主人接受某个人的报价，出价转给主人，猫转给报价的人
+		#[weight = T::DbWeight::get().reads_writes(7, 10) + 80_000_000]
+		pub fn accept_offer(origin, kitty_id: T::KittyIndex, buyer: T::AccountId){
+			let sender = ensure_signed(origin)?;
+			let owner = Self::kitty_owners(kitty_id).ok_or( Error::<T>::KittyNotExists )?;
+			ensure!(owner == sender, Error::<T>::NotKittyOwner);
+			ensure!(!<Auctions::<T>>::contains_key(kitty_id), Error::<T>::KittyOnAuction);
+			ensure!(buyer != sender, Error::<T>::AlreadyOwned);
+			let offer = Self::offers(kitty_id, &buyer).ok_or( Error::<T>::OfferNotExists )?;
+			ensure!(<frame_system::Module<T>>::block_number() < offer.expires_at, Error::<T>::OfferExpired);
+
+			// 出价从买家的质押直接转到卖家的可用余额，买家质押的猫的押金留下，卖家的押金释放
+			// 买家的质押被其他模块动过、不够付出价的时候失败，不会修改任何数据
+			ensure!(Self::pay_from_reserve(&buyer, &sender, offer.amount), Error::<T>::MoneyNotEnough);
+
+			<Offers::<T>>::remove(kitty_id, &buyer);
+			Self::decrease_offer_count(kitty_id);
+			Self::transfer_ownership(&sender, &buyer, kitty_id, offer.deposit);
+
+			Self::deposit_event(RawEvent::OfferAccepted(sender, buyer, kitty_id, offer.amount));
+		}
+		// 释放猫并退还押金，对这只猫的报价也全部退还，家族关系保留下来，在 DeadKitties 里边记录为已经释放
+		// 报价的数量不超过 MaxOffersPerKitty，按照最多的报价收取权重
+		#[weight = T::DbWeight::get().reads_writes(4, 9)
+			.saturating_add(T::DbWeight::get().reads_writes(2, 2).saturating_mul(T::MaxOffersPerKitty::get() as Weight))
+			.saturating_add(60_000_000)]
+		pub fn release(origin, kitty_id: T::KittyIndex){
+			let sender = ensure_signed(origin)?;
+			let owner = Self::kitty_owners(kitty_id).ok_or( Error::<T>::KittyNotExists )?;
+			ensure!(owner == sender, Error::<T>::NotKittyOwner);
+			ensure!(!<Auctions::<T>>::contains_key(kitty_id), Error::<T>::KittyOnAuction);
+
+			let deposit = match <KittyDeposits::<T>>::take(kitty_id) {
+				Some((holder, deposit)) => {
+					T::Currency::unreserve(&holder, deposit);
+					deposit
+				}
+				None => Zero::zero(),
+			};
+			for (bidder, offer) in <Offers::<T>>::drain_prefix(kitty_id) {
+				T::Currency::unreserve(&bidder, offer.amount.saturating_add(offer.deposit));
+			}
+			<OfferCount::<T>>::remove(kitty_id);
+
+			<Kitties::<T>>::remove(kitty_id);
+			<KittyOwners::<T>>::remove(kitty_id);
+			<OwnedKitties::<T>>::remove(&sender, kitty_id);
+			<KittyPrices::<T>>::remove(kitty_id);
+			<PendingGifts::<T>>::remove(kitty_id);
+			<DeadKitties::<T>>::insert(kitty_id, <frame_system::Module<T>>::block_number());
+
+			Self::deposit_event(RawEvent::Released(sender, kitty_id, deposit));
+		}
+		// NewKittyReserve 修改之后，把指定的猫的押金调整为新的数量，押金多了退还差额，少了补质押差额
+		// 余额不够补质押的猫保持原来的押金，不影响其他的猫
+		#[weight = T::DbWeight::get().reads_writes(2, 2).saturating_mul(kitty_ids.len() as Weight)]
+		pub fn rebase_deposits(origin, kitty_ids: Vec<T::KittyIndex>){
+			T::DepositOrigin::ensure_origin(origin)?;
+			let new_deposit = T::NewKittyReserve::get();
+
+			for kitty_id in kitty_ids {
+				let (holder, old_deposit) = match Self::kitty_deposits(kitty_id) {
+					Some(record) => record,
+					None => continue,
+				};
+				if old_deposit == new_deposit {
+					continue;
+				}
+				if new_deposit > old_deposit {
+					if T::Currency::reserve(&holder, new_deposit - old_deposit).is_err() {
+						continue;
+					}
+				} else {
+					T::Currency::unreserve(&holder, old_deposit - new_deposit);
+				}
+				<KittyDeposits::<T>>::insert(kitty_id, (&holder, new_deposit));
+
+				Self::deposit_event(RawEvent::DepositRebased(kitty_id, holder, old_deposit, new_deposit));
+			}
 		}
 	}
 }
 
 impl<T: Config> Module<T> {
+	// 结算拍卖：有人出价就把出价付给卖家，猫和押金转给买家，没人出价就流拍
+	fn settle_auction(kitty_id: T::KittyIndex, auction: AuctionOf<T>) {
+		let seller = auction.seller;
+		match auction.highest_bid {
+			// 出价直接从买家的质押转到卖家的可用余额，买家质押的猫的押金留下，卖家的押金释放
+			Some(bid) if Self::pay_from_reserve(&bid.bidder, &seller, bid.amount) => {
+				Self::transfer_ownership(&seller, &bid.bidder, kitty_id, bid.deposit);
+
+				Self::deposit_event(RawEvent::AuctionSettled(seller, bid.bidder, kitty_id, bid.amount));
+			}
+			// 买家的质押被其他模块动过，不够付出价，按照流拍处理，剩下的质押退还给买家
+			Some(bid) => {
+				T::Currency::unreserve(&bid.bidder, bid.amount.saturating_add(bid.deposit));
+				Self::deposit_event(RawEvent::AuctionUnsold(seller, kitty_id));
+			}
+			None => Self::deposit_event(RawEvent::AuctionUnsold(seller, kitty_id)),
+		}
+	}
+
+	// 从买家的质押里边把价格转到卖家的可用余额，只有全部转过去才算成功，否则什么都不修改
+	fn pay_from_reserve(buyer: &T::AccountId, seller: &T::AccountId, amount: BalanceOf<T>) -> bool {
+		with_transaction(|| match T::Currency::repatriate_reserved(buyer, seller, amount, BalanceStatus::Free) {
+			Ok(remainder) if remainder.is_zero() => TransactionOutcome::Commit(true),
+			_ => TransactionOutcome::Rollback(false),
+		})
+	}
+
+	// 报价被撤回、接受之后减少报价的数量，为 0 的时候删除记录
+	fn decrease_offer_count(kitty_id: T::KittyIndex) {
+		<OfferCount::<T>>::mutate_exists(kitty_id, |count| {
+			*count = count.map(|c| c.saturating_sub(1)).filter(|c| *c > 0);
+		});
+	}
+
+	// 所有的所有权变更都要通过这里，保证 KittyOwners 和 OwnedKitties 一致，转让之后之前的挂单和赠送也失效
+	// deposit 是新的拥有者已经质押好的押金，之前记录的押金按照记录解质押
+	fn transfer_ownership(from: &T::AccountId, to: &T::AccountId, kitty_id: T::KittyIndex, deposit: BalanceOf<T>) {
+		if let Some((holder, old_deposit)) = <KittyDeposits::<T>>::take(kitty_id) {
+			T::Currency::unreserve(&holder, old_deposit);
+		}
+		<KittyDeposits::<T>>::insert(kitty_id, (to, deposit));
+		<KittyOwners::<T>>::insert(kitty_id, to);
+		<OwnedKitties::<T>>::remove(from, kitty_id);
+		<OwnedKitties::<T>>::insert(to, kitty_id, kitty_id);
+		<KittyPrices::<T>>::remove(kitty_id);
+		<PendingGifts::<T>>::remove(kitty_id);
+	}
+
+	// 检查 KittyOwners 和 OwnedKitties 是否一致，以及每个账号的质押是否足够，返回所有不一致的地方
+	pub fn check_invariants() -> Vec<InvariantViolation<T::AccountId, T::KittyIndex>> {
+		let mut violations = Vec::new();
+
+		for (kitty_id, owner) in <KittyOwners::<T>>::iter() {
+			if Self::owned_kitties(&owner, kitty_id) != Some(kitty_id) {
+				violations.push(InvariantViolation::MissingOwnedEntry(owner, kitty_id));
+			}
+		}
+		for (account, kitty_id, _) in <OwnedKitties::<T>>::iter() {
+			if Self::kitty_owners(kitty_id).as_ref() != Some(&account) {
+				violations.push(InvariantViolation::StaleOwnedEntry(account, kitty_id));
+			}
+		}
+
+		// 每个账号至少要质押：记录的猫的押金，以及最高出价和报价（连同成交之后的猫的押金）
+		// 其他模块也可能质押同一个账号的资产，所以只检查质押够不够，不要求相等
+		let mut expected: BTreeMap<T::AccountId, BalanceOf<T>> = BTreeMap::new();
+		for (kitty_id, owner) in <KittyOwners::<T>>::iter() {
+			match Self::kitty_deposits(kitty_id) {
+				Some((holder, deposit)) if holder == owner => {
+					let reserved = expected.entry(holder).or_default();
+					*reserved = reserved.saturating_add(deposit);
+				}
+				_ => violations.push(InvariantViolation::DepositMismatch(owner, kitty_id)),
+			}
+		}
+		for (_, auction) in <Auctions::<T>>::iter() {
+			if let Some(bid) = auction.highest_bid {
+				let reserved = expected.entry(bid.bidder).or_default();
+				*reserved = reserved.saturating_add(bid.amount).saturating_add(bid.deposit);
+			}
+		}
+		for (_, bidder, offer) in <Offers::<T>>::iter() {
+			let reserved = expected.entry(bidder).or_default();
+			*reserved = reserved.saturating_add(offer.amount).saturating_add(offer.deposit);
+		}
+		for (account, reserved) in expected {
+			if T::Currency::reserved_balance(&account) < reserved {
+				violations.push(InvariantViolation::InsufficientReserve(account));
+			}
+		}
+
+		violations
+	}
+
 	// 获取下一个
 	fn next_kitty_id() -> sp_std::result::Result<T::KittyIndex, DispatchError>{
 		let kitty_id = Self::kitties_count();
@@ -208,15 +717,16 @@ impl<T: Config> Module<T> {
 		let kitty = Kitty(dna);
 
 		// 质押指定数量的资产，如果资产质押失败，会报错【质押会触发时间，做测试的时候需要注意】
-		T::Currency::reserve(&sender, T::NewKittyReserve::get()).map_err(|_| Error::<T>::MoneyNotEnough )?;
+		let deposit = T::NewKittyReserve::get();
+		T::Currency::reserve(&sender, deposit).map_err(|_| Error::<T>::MoneyNotEnough )?;
 
-		Self::insert_kitty(&sender, kitty_id, kitty, None);
+		Self::insert_kitty(&sender, kitty_id, kitty, deposit, None);
 
 		Ok(kitty_id)
 	}
 
 	// 插入一个 kitty ，因为父母可能不存在，所以parent 需要用 Option
-	fn insert_kitty(owner : &T::AccountId, kitty_id : T::KittyIndex, kitty : Kitty, parent: Option<(T::KittyIndex, T::KittyIndex)> ){
+	fn insert_kitty(owner : &T::AccountId, kitty_id : T::KittyIndex, kitty : Kitty, deposit: BalanceOf<T>, parent: Option<(T::KittyIndex, T::KittyIndex)> ){
 		// 保存 Kitty 
 		<Kitties::<T>>::insert(kitty_id, kitty);
 		// 更新 Kitty 数量，当前 ID+1
@@ -225,6 +735,8 @@ impl<T: Config> Module<T> {
 		<KittyOwners::<T>>::insert(kitty_id, owner);
 		// 保存拥有者拥有的 Kitty 数据
 		<OwnedKitties::<T>>::insert(owner, kitty_id, kitty_id);
+		// 记录创建的时候实际质押的押金
+		<KittyDeposits::<T>>::insert(kitty_id, (owner, deposit));
 		// 保存 Kitty 的父母相关的数据，因为无父母的情况，就不管了
 		match parent {
 			Some((parent_id1, parent_id2)) =>{
@@ -254,29 +766,44 @@ impl<T: Config> Module<T> {
 
 		let kitty_1 = Self::kitties(kitty_id1).ok_or( Error::<T>::KittyNotExists )?;
 		let kitty_2 = Self::kitties(kitty_id2).ok_or( Error::<T>::KittyNotExists )?;
+		// 需要一只公猫和一只母猫
+		ensure!( kitty_1.gender() != kitty_2.gender(), Error::<T>::RequiredMaleAndFemale);
+		// 父母都要休息好，并且没有达到最多的繁殖次数
+		let now = <frame_system::Module<T>>::block_number();
+		ensure!( Self::kitty_cooldowns(kitty_id1) <= now && Self::kitty_cooldowns(kitty_id2) <= now, Error::<T>::KittyTired);
+		ensure!( Self::kitty_litters(kitty_id1) < T::MaxLitters::get() && Self::kitty_litters(kitty_id2) < T::MaxLitters::get(), Error::<T>::TooManyLitters);
 
 		let kitty_id = Self::next_kitty_id()?;
 
-		let kitty1_dna = kitty_1.0;
-		let kitty2_dna = kitty_2.0;
 		let selector = Self::random_value(&owner);
+		let kitty = Kitty(genetics::breed_dna(&kitty_1.0, &kitty_2.0, &selector));
+		let generation = Self::kitty_generations(kitty_id1)
+			.max(Self::kitty_generations(kitty_id2))
+			.saturating_add(1);
 
-		let mut new_dna = [0u8; 16];
+		let deposit = T::NewKittyReserve::get();
+		T::Currency::reserve(&owner, deposit).map_err(|_| Error::<T>::MoneyNotEnough )?;
 
-		for i in 0..kitty1_dna.len() {
-			new_dna[i] = combine_dna(kitty1_dna[i], kitty2_dna[i], selector[i]);
-		}
+		Self::insert_kitty(owner, kitty_id, kitty, deposit, Some((kitty_id1, kitty_id2)));
+		<KittyGenerations::<T>>::insert(kitty_id, generation);
+		Self::start_cooldown(kitty_id1, now);
+		Self::start_cooldown(kitty_id2, now);
 
-		let kitty = Kitty(new_dna);
-
-		T::Currency::reserve(&owner, T::NewKittyReserve::get()).map_err(|_| Error::<T>::MoneyNotEnough )?;
+		Ok(kitty_id)
+	}
 
-		Self::insert_kitty(owner, kitty_id, kitty, Some((kitty_id1, kitty_id2)));
+	// 繁殖之后增加繁殖次数，并且开始休息，代数越大、繁殖次数越多，休息的时间越长
+	fn start_cooldown(kitty_id: T::KittyIndex, now: T::BlockNumber) {
+		let litters = Self::kitty_litters(kitty_id).saturating_add(1);
+		let factor = Self::kitty_generations(kitty_id).saturating_add(litters).saturating_add(1);
+		let cooldown = T::BaseBreedingCooldown::get().saturating_mul(factor.into());
 
-		Ok(kitty_id)
+		<KittyLitters::<T>>::insert(kitty_id, litters);
+		<KittyCooldowns::<T>>::insert(kitty_id, now.saturating_add(cooldown));
 	}
-}
 
-fn combine_dna(dna1: u8, dna2: u8, selector: u8) -> u8{
-	(selector & dna1 ) | (!selector & dna2)
+	// 查询解析之后的 Kitty：性别、代数、稀有度和外观特征，猫不存在或者已经释放返回 None
+	pub fn kitty_info(kitty_id: T::KittyIndex) -> Option<KittyInfo> {
+		Self::kitties(kitty_id).map(|kitty| kitty.info(Self::kitty_generations(kitty_id)))
+	}
 }
\ No newline at end of file
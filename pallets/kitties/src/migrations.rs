@@ -0,0 +1,113 @@
+//! pallet_kitties 的存储迁移
+
+use super::*;
+
+// V1_0_0 迁移到 V2_0_0：之前的 buy 没有更新 OwnedKitties，卖出去的猫还留在卖家的索引里边，买家的索引里边没有
+// 以 KittyOwners 为准，清空 OwnedKitties 之后重新建立拥有者索引
+pub fn migrate_to_v2<T: Config>() -> Weight {
+	if StorageVersion::get() != Releases::V1_0_0 {
+		return T::DbWeight::get().reads(1);
+	}
+
+	let removed = <OwnedKitties::<T>>::drain().count() as Weight;
+	let mut rebuilt: Weight = 0;
+	for (kitty_id, owner) in <KittyOwners::<T>>::iter() {
+		<OwnedKitties::<T>>::insert(&owner, kitty_id, kitty_id);
+		rebuilt += 1;
+	}
+
+	StorageVersion::put(Releases::V2_0_0);
+	T::DbWeight::get().reads_writes(1 + removed + rebuilt, 1 + removed + rebuilt)
+}
+
+// V2_0_0 迁移到 V3_0_0：之前没有记录每只猫的押金，拥有者质押的一直是当时的 NewKittyReserve
+// 假设升级的时候 NewKittyReserve 还没有修改，按照当前的数量给每只猫补上押金记录，质押的账号是当前的拥有者
+pub fn migrate_to_v3<T: Config>() -> Weight {
+	if StorageVersion::get() != Releases::V2_0_0 {
+		return T::DbWeight::get().reads(1);
+	}
+
+	let deposit = T::NewKittyReserve::get();
+	let mut migrated: Weight = 0;
+	for (kitty_id, owner) in <KittyOwners::<T>>::iter() {
+		<KittyDeposits::<T>>::insert(kitty_id, (owner, deposit));
+		migrated += 1;
+	}
+
+	StorageVersion::put(Releases::V3_0_0);
+	T::DbWeight::get().reads_writes(1 + migrated, 1 + migrated)
+}
+
+// V3_0_0 迁移到 V4_0_0：之前没有记录代数
+// 父母的编号一定比孩子小，按照编号从小到大，代数是父母中代数大的那只加一
+// 已经释放的猫的家族关系还保留着，也一起计算，保证后代的代数正确
+pub fn migrate_to_v4<T: Config>() -> Weight {
+	if StorageVersion::get() != Releases::V3_0_0 {
+		return T::DbWeight::get().reads(1);
+	}
+
+	let count = Module::<T>::kitties_count();
+	let mut reads: Weight = 2;
+	let mut writes: Weight = 1;
+	let mut kitty_id = T::KittyIndex::zero();
+	while kitty_id < count {
+		if let Some((parent_id1, parent_id2)) = Module::<T>::kitty_parents(kitty_id) {
+			let generation = Module::<T>::kitty_generations(parent_id1)
+				.max(Module::<T>::kitty_generations(parent_id2))
+				.saturating_add(1);
+			<KittyGenerations::<T>>::insert(kitty_id, generation);
+			reads += 2;
+			writes += 1;
+		}
+		reads += 1;
+		kitty_id = kitty_id.saturating_add(One::one());
+	}
+
+	StorageVersion::put(Releases::V4_0_0);
+	T::DbWeight::get().reads_writes(reads, writes)
+}
+
+// V4_0_0 迁移到 V5_0_0：加上 MaxOffersPerKitty 之前没有记录每只猫的报价数量，按照 Offers 统计
+pub fn migrate_to_v5<T: Config>() -> Weight {
+	if StorageVersion::get() != Releases::V4_0_0 {
+		return T::DbWeight::get().reads(1);
+	}
+
+	let mut offer_counts: BTreeMap<T::KittyIndex, u32> = BTreeMap::new();
+	let mut reads: Weight = 1;
+	for (kitty_id, _, _) in <Offers::<T>>::iter() {
+		*offer_counts.entry(kitty_id).or_default() += 1;
+		reads += 1;
+	}
+	let writes = 1 + offer_counts.len() as Weight;
+	for (kitty_id, offers) in offer_counts {
+		<OfferCount::<T>>::insert(kitty_id, offers);
+	}
+
+	StorageVersion::put(Releases::V5_0_0);
+	T::DbWeight::get().reads_writes(reads, writes)
+}
+
+// V5_0_0 迁移到 V6_0_0：加上 MaxLitters 之前没有记录繁殖次数，每次繁殖都会生一个孩子，繁殖次数就是孩子的数量
+pub fn migrate_to_v6<T: Config>() -> Weight {
+	if StorageVersion::get() != Releases::V5_0_0 {
+		return T::DbWeight::get().reads(1);
+	}
+
+	let count = Module::<T>::kitties_count();
+	let mut reads: Weight = 2;
+	let mut writes: Weight = 1;
+	let mut kitty_id = T::KittyIndex::zero();
+	while kitty_id < count {
+		let litters = <KittyChildren::<T>>::iter_prefix(kitty_id).count() as u32;
+		if litters > 0 {
+			<KittyLitters::<T>>::insert(kitty_id, litters);
+			writes += 1;
+		}
+		reads += 1 + litters as Weight;
+		kitty_id = kitty_id.saturating_add(One::one());
+	}
+
+	StorageVersion::put(Releases::V6_0_0);
+	T::DbWeight::get().reads_writes(reads, writes)
+}
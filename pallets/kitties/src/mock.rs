@@ -2,8 +2,9 @@ use crate as pallet_kitties;
 use balances;
 use sp_core::H256;
 use frame_support::{
-	parameter_types
+	parameter_types, traits::Get,
 };
+use std::cell::RefCell;
 use sp_runtime::{
 	traits::{BlakeTwo256, IdentityLookup}, testing::Header,
 };
@@ -21,7 +22,7 @@ frame_support::construct_runtime!(
 	{
 		System: frame_system::{Module, Call, Config, Storage, Event<T>},
 		Balances: balances::{Module, Call, Storage, Config<T>, Event<T>},
-		KittiesModule: pallet_kitties::{Module, Call, Storage, Event<T>},
+		KittiesModule: pallet_kitties::{Module, Call, Config, Storage, Event<T>},
 	}
 );
 
@@ -71,8 +72,29 @@ impl balances::Config for Test {
 
 type Randomness = pallet_randomness_collective_flip::Module<Test>;
 
+// 测试 rebase_deposits 的时候需要修改创建 Kitty 的押金，所以不用 parameter_types 定义
+thread_local! {
+	static NEW_KITTY_RESERVE: RefCell<u64> = RefCell::new(5_000);
+}
+pub struct NewKittyReserve;
+impl NewKittyReserve {
+	pub fn set(amount: u64) {
+		NEW_KITTY_RESERVE.with(|v| *v.borrow_mut() = amount);
+	}
+}
+impl Get<u64> for NewKittyReserve {
+	fn get() -> u64 {
+		NEW_KITTY_RESERVE.with(|v| *v.borrow())
+	}
+}
+
 parameter_types! {
-	pub const NewKittyReserve: u64 = 5_000;
+	pub const MaxAuctionDuration: u64 = 100;
+	pub const MaxAuctionsPerBlock: u32 = 2;
+	pub const BaseBreedingCooldown: u64 = 5;
+	pub const MaxLitters: u32 = 2;
+	pub const MaxOffersPerKitty: u32 = 2;
+	pub const MaxOfferDuration: u64 = 50;
 }
 impl pallet_kitties::Config for Test {
 	type Event = Event;
@@ -80,6 +102,13 @@ impl pallet_kitties::Config for Test {
 	type KittyIndex = u32;
 	type NewKittyReserve = NewKittyReserve;
 	type Currency = balances::Module<Self>;
+	type MaxAuctionDuration = MaxAuctionDuration;
+	type MaxAuctionsPerBlock = MaxAuctionsPerBlock;
+	type DepositOrigin = frame_system::EnsureRoot<u64>;
+	type BaseBreedingCooldown = BaseBreedingCooldown;
+	type MaxLitters = MaxLitters;
+	type MaxOffersPerKitty = MaxOffersPerKitty;
+	type MaxOfferDuration = MaxOfferDuration;
 }
 
 // Build genesis storage according to the mock runtime.
@@ -93,6 +122,7 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 		balances: vec![(1, 10000000000), (2, 110000000), (3, 1200000000), (4, 1300000000), (5, 1400000000)],
 	}.assimilate_storage(&mut t)
 	.unwrap();
+	pallet_kitties::GenesisConfig::default().assimilate_storage::<Test>(&mut t).unwrap();
 	let mut ext: sp_io::TestExternalities = t.into();
 	ext.execute_with(|| System::set_block_number(1));
 	ext
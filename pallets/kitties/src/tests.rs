@@ -1,9 +1,9 @@
 use super::*;
 use crate::mock::{
-	Event,System,Origin,KittiesModule,new_test_ext,Test
+	Event,System,Origin,KittiesModule,Balances,NewKittyReserve,new_test_ext,Test
 };
 // use crate::{Event, Error, mock::*};
-use frame_support::{assert_noop, assert_ok, traits::{OnFinalize, OnInitialize}};
+use frame_support::{assert_noop, assert_ok, traits::{OnFinalize, OnInitialize, OnRuntimeUpgrade}};
 
 fn run_to_block( n: u64) {
 	while System::block_number() < n {
@@ -15,6 +15,13 @@ fn run_to_block( n: u64) {
 	}
 }
 
+// 修改猫的性别，繁殖需要一只公猫和一只母猫
+fn set_gender(kitty_id: u32, gender: Gender) {
+	Kitties::<Test>::mutate(kitty_id, |kitty| if let Some(kitty) = kitty {
+		kitty.0[0] = match gender { Gender::Male => 0, Gender::Female => 1 };
+	});
+}
+
 // // 测试创建一个 Kitty
 #[test]
 fn create_kitty_works(){
@@ -48,11 +55,58 @@ fn transfer_kitty_works(){
 
 		assert_ok!(KittiesModule::transfer( Origin::signed(1), 2, 0 ) );
 
-		// 因为有创建时候的（质押+创建），转让时候的（质押+解质押+转让），所以总共会触发个五个事件，这里只监控第五个
+		// 因为有创建时候的（质押+创建），所以发起赠送是第三个事件，接收人确认之前不会质押接收人的代币
+		assert_eq!(
+			System::events()[2].event,
+			Event::pallet_kitties(RawEvent::GiftOffered( 1u64 ,2u64, 0) )
+		);
+		assert_eq!( KittiesModule::kitty_owners(0), Some(1) );
+		assert_eq!( Balances::reserved_balance(2), 0 );
+
+		assert_ok!(KittiesModule::accept_gift( Origin::signed(2), 0 ) );
+
 		assert_eq!(
-			System::events()[4].event,
+			System::events().last().unwrap().event,
 			Event::pallet_kitties(RawEvent::Transferred( 1u64 ,2u64, 0) )
 		);
+		assert_eq!( KittiesModule::kitty_owners(0), Some(2) );
+		assert_eq!( KittiesModule::pending_gifts(0), None );
+		assert_eq!( Balances::reserved_balance(1), 0 );
+		assert_eq!( Balances::reserved_balance(2), 5_000 );
+	});
+}
+
+// 只有接收人可以接收赠送，赠送人和接收人都可以取消赠送
+#[test]
+fn gift_accept_and_cancel(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		assert_noop!( KittiesModule::accept_gift( Origin::signed(2), 0 ), Error::<Test>::GiftNotExists );
+
+		assert_ok!( KittiesModule::transfer( Origin::signed(1), 2, 0 ) );
+		assert_noop!( KittiesModule::accept_gift( Origin::signed(3), 0 ), Error::<Test>::NotGiftRecipient );
+		assert_noop!( KittiesModule::cancel_gift( Origin::signed(3), 0 ), Error::<Test>::NotKittyOwner );
+
+		// 接收人拒绝
+		assert_ok!( KittiesModule::cancel_gift( Origin::signed(2), 0 ) );
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_kitties(RawEvent::GiftCancelled( 1u64 ,2u64, 0) )
+		);
+		assert_noop!( KittiesModule::accept_gift( Origin::signed(2), 0 ), Error::<Test>::GiftNotExists );
+
+		// 赠送人撤回
+		assert_ok!( KittiesModule::transfer( Origin::signed(1), 2, 0 ) );
+		assert_ok!( KittiesModule::cancel_gift( Origin::signed(1), 0 ) );
+		assert_eq!( KittiesModule::pending_gifts(0), None );
+
+		// 所有权变更之后赠送失效
+		assert_ok!( KittiesModule::transfer( Origin::signed(1), 2, 0 ) );
+		assert_ok!( KittiesModule::ask( Origin::signed(1), 0, Some(1_000) ) );
+		assert_ok!( KittiesModule::buy( Origin::signed(3), 0, 1_000 ) );
+		assert_noop!( KittiesModule::accept_gift( Origin::signed(2), 0 ), Error::<Test>::GiftNotExists );
+		assert_eq!( KittiesModule::kitty_owners(0), Some(3) );
 	});
 }
 
@@ -92,6 +146,8 @@ fn breed_kitty_work(){
 		run_to_block(10);
 		let _ = KittiesModule::create( Origin::signed(1) );
 		let _ = KittiesModule::create( Origin::signed(1) );
+		set_gender(0, Gender::Male);
+		set_gender(1, Gender::Female);
 
 		assert_ok!( KittiesModule::breed( Origin::signed(1), 0, 1 ) );
 		
@@ -151,4 +207,647 @@ fn ask_price_fail_when_not_owner(){
 		let _ = KittiesModule::create( Origin::signed(1) );
 		assert_noop!( KittiesModule::ask( Origin::signed(2), 0, Some(5_000_000_000) ) , Error::<Test>::NotKittyOwner);
 	});
-}
\ No newline at end of file
+}
+
+// 拍卖成功：出价被超过的退还质押，结束的区块结算，猫和出价分别转给买家和卖家
+#[test]
+fn auction_settles_to_highest_bidder(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		assert_ok!( KittiesModule::create_auction( Origin::signed(1), 0, 1_000, 100, 20 ) );
+
+		assert_ok!( KittiesModule::bid( Origin::signed(2), 0, 1_000 ) );
+		assert_eq!( Balances::reserved_balance(2), 6_000 );
+		assert_ok!( KittiesModule::bid( Origin::signed(3), 0, 1_100 ) );
+		// 被超过的出价人拿回全部质押
+		assert_eq!( Balances::reserved_balance(2), 0 );
+		assert_eq!( Balances::reserved_balance(3), 6_100 );
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_kitties( RawEvent::BidPlaced( 3u64, 0, 1_100 ) )
+		);
+
+		// 还没到结束的区块，不会结算
+		run_to_block(20);
+		assert_eq!( KittiesModule::kitty_owners(0), Some(1) );
+
+		run_to_block(21);
+		assert_eq!( KittiesModule::kitty_owners(0), Some(3) );
+		assert_eq!( KittiesModule::owned_kitties(3, 0), Some(0) );
+		assert_eq!( KittiesModule::owned_kitties(1, 0), None );
+		assert_eq!( KittiesModule::auctions(0), None );
+		assert!( KittiesModule::auction_ends(20).is_empty() );
+		// 买家只留下猫的押金，卖家收到出价并释放押金
+		assert_eq!( Balances::reserved_balance(3), 5_000 );
+		assert_eq!( Balances::free_balance(3), 1_200_000_000 - 6_100 );
+		assert_eq!( Balances::reserved_balance(1), 0 );
+		assert_eq!( Balances::free_balance(1), 10_000_000_000 + 1_100 );
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_kitties( RawEvent::AuctionSettled( 1u64, 3u64, 0, 1_100 ) )
+		);
+	});
+}
+
+// 没有人出价的拍卖到期流拍，猫还是卖家的
+#[test]
+fn auction_unsold_without_bids(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		assert_ok!( KittiesModule::create_auction( Origin::signed(1), 0, 1_000, 100, 15 ) );
+
+		run_to_block(16);
+		assert_eq!( KittiesModule::kitty_owners(0), Some(1) );
+		assert_eq!( KittiesModule::auctions(0), None );
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_kitties( RawEvent::AuctionUnsold( 1u64, 0 ) )
+		);
+	});
+}
+
+// 发起拍卖失败：不是主人、结束区块不对、同一个区块结束的拍卖太多
+#[test]
+fn create_auction_failed(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		let _ = KittiesModule::create( Origin::signed(1) );
+		let _ = KittiesModule::create( Origin::signed(1) );
+
+		assert_noop!( KittiesModule::create_auction( Origin::signed(2), 0, 1_000, 100, 20 ), Error::<Test>::NotKittyOwner );
+		assert_noop!( KittiesModule::create_auction( Origin::signed(1), 0, 1_000, 100, 10 ), Error::<Test>::InvalidAuctionEnd );
+		assert_noop!( KittiesModule::create_auction( Origin::signed(1), 0, 1_000, 100, 111 ), Error::<Test>::InvalidAuctionEnd );
+
+		assert_ok!( KittiesModule::create_auction( Origin::signed(1), 0, 1_000, 100, 20 ) );
+		assert_noop!( KittiesModule::create_auction( Origin::signed(1), 0, 1_000, 100, 30 ), Error::<Test>::KittyOnAuction );
+		assert_ok!( KittiesModule::create_auction( Origin::signed(1), 1, 1_000, 100, 20 ) );
+		assert_noop!( KittiesModule::create_auction( Origin::signed(1), 2, 1_000, 100, 20 ), Error::<Test>::TooManyAuctions );
+	});
+}
+
+// 出价失败：出价太低、对自己的猫出价、拍卖已经结束
+#[test]
+fn bid_failed(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		assert_noop!( KittiesModule::bid( Origin::signed(2), 0, 1_000 ), Error::<Test>::AuctionNotExists );
+		assert_ok!( KittiesModule::create_auction( Origin::signed(1), 0, 1_000, 100, 20 ) );
+
+		assert_noop!( KittiesModule::bid( Origin::signed(2), 0, 999 ), Error::<Test>::BidTooLow );
+		assert_noop!( KittiesModule::bid( Origin::signed(1), 0, 1_000 ), Error::<Test>::BidOnOwnKitty );
+		assert_ok!( KittiesModule::bid( Origin::signed(2), 0, 1_000 ) );
+		assert_noop!( KittiesModule::bid( Origin::signed(3), 0, 1_099 ), Error::<Test>::BidTooLow );
+		assert_noop!( KittiesModule::bid( Origin::signed(2), 0, 200_000_000 ), Error::<Test>::MoneyNotEnough );
+	});
+}
+
+// 最小加价幅度为 0 的时候，相同的出价不能替换领先的人
+#[test]
+fn bid_must_exceed_highest_without_increment(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		assert_ok!( KittiesModule::create_auction( Origin::signed(1), 0, 1_000, 0, 20 ) );
+
+		assert_ok!( KittiesModule::bid( Origin::signed(2), 0, 1_000 ) );
+		assert_noop!( KittiesModule::bid( Origin::signed(3), 0, 1_000 ), Error::<Test>::BidTooLow );
+		assert_ok!( KittiesModule::bid( Origin::signed(3), 0, 1_001 ) );
+	});
+}
+
+// 买家的质押不够付出价的时候，拍卖按照流拍处理，猫还是卖家的
+#[test]
+fn auction_unsold_when_bid_reserve_slashed(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		assert_ok!( KittiesModule::create_auction( Origin::signed(1), 0, 1_000, 100, 15 ) );
+		assert_ok!( KittiesModule::bid( Origin::signed(2), 0, 1_000 ) );
+		let _ = Balances::slash_reserved( &2, 5_500 );
+
+		run_to_block(16);
+		assert_eq!( KittiesModule::kitty_owners(0), Some(1) );
+		assert_eq!( KittiesModule::kitty_deposits(0), Some((1, 5_000)) );
+		assert_eq!( Balances::reserved_balance(2), 0 );
+		assert_eq!( Balances::free_balance(1), 10_000_000_000 - 5_000 );
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_kitties( RawEvent::AuctionUnsold( 1u64, 0 ) )
+		);
+		assert!( KittiesModule::check_invariants().is_empty() );
+	});
+}
+
+// 拍卖中的猫不能转让、挂单和购买，有人出价之后不能取消
+#[test]
+fn kitty_locked_during_auction(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		assert_ok!( KittiesModule::ask( Origin::signed(1), 0, Some(5_000) ) );
+		assert_ok!( KittiesModule::create_auction( Origin::signed(1), 0, 1_000, 100, 20 ) );
+		// 发起拍卖会取消之前的挂单
+		assert_eq!( KittiesModule::kitty_prices(0), None );
+
+		assert_noop!( KittiesModule::transfer( Origin::signed(1), 2, 0 ), Error::<Test>::KittyOnAuction );
+		assert_noop!( KittiesModule::ask( Origin::signed(1), 0, Some(5_000) ), Error::<Test>::KittyOnAuction );
+
+		assert_noop!( KittiesModule::cancel_auction( Origin::signed(2), 0 ), Error::<Test>::NotKittyOwner );
+		assert_ok!( KittiesModule::cancel_auction( Origin::signed(1), 0 ) );
+		assert!( KittiesModule::auction_ends(20).is_empty() );
+
+		assert_ok!( KittiesModule::create_auction( Origin::signed(1), 0, 1_000, 100, 20 ) );
+		assert_ok!( KittiesModule::bid( Origin::signed(2), 0, 1_000 ) );
+		assert_noop!( KittiesModule::cancel_auction( Origin::signed(1), 0 ), Error::<Test>::AuctionHasBids );
+	});
+}
+
+// 接受报价成功：没有挂单的猫也可以报价，重复报价会替换之前的报价
+#[test]
+fn accept_offer_work(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+
+		assert_ok!( KittiesModule::make_offer( Origin::signed(2), 0, 1_000, 20 ) );
+		assert_ok!( KittiesModule::make_offer( Origin::signed(2), 0, 2_000, 20 ) );
+		assert_eq!( Balances::reserved_balance(2), 7_000 );
+		assert_eq!( KittiesModule::offers(0, 2), Some(Offer { amount: 2_000, deposit: 5_000, expires_at: 20 }) );
+
+		assert_noop!( KittiesModule::accept_offer( Origin::signed(2), 0, 2 ), Error::<Test>::NotKittyOwner );
+		assert_ok!( KittiesModule::accept_offer( Origin::signed(1), 0, 2 ) );
+
+		assert_eq!( KittiesModule::kitty_owners(0), Some(2) );
+		assert_eq!( KittiesModule::owned_kitties(2, 0), Some(0) );
+		assert_eq!( KittiesModule::owned_kitties(1, 0), None );
+		assert_eq!( KittiesModule::offers(0, 2), None );
+		assert_eq!( Balances::reserved_balance(2), 5_000 );
+		assert_eq!( Balances::free_balance(2), 110_000_000 - 7_000 );
+		assert_eq!( Balances::reserved_balance(1), 0 );
+		assert_eq!( Balances::free_balance(1), 10_000_000_000 + 2_000 );
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_kitties( RawEvent::OfferAccepted( 1u64, 2u64, 0, 2_000 ) )
+		);
+	});
+}
+
+// 撤回报价，释放质押；过期的报价不能被接受，但可以撤回
+#[test]
+fn withdraw_offer_work(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+
+		assert_noop!( KittiesModule::make_offer( Origin::signed(1), 0, 1_000, 20 ), Error::<Test>::AlreadyOwned );
+		assert_noop!( KittiesModule::make_offer( Origin::signed(2), 0, 0, 20 ), Error::<Test>::ZeroOffer );
+		assert_noop!( KittiesModule::make_offer( Origin::signed(2), 0, 1_000, 10 ), Error::<Test>::InvalidOfferExpiry );
+		assert_noop!( KittiesModule::make_offer( Origin::signed(2), 0, 1_000, 61 ), Error::<Test>::InvalidOfferExpiry );
+		assert_noop!( KittiesModule::make_offer( Origin::signed(2), 0, 200_000_000, 20 ), Error::<Test>::MoneyNotEnough );
+		assert_noop!( KittiesModule::withdraw_offer( Origin::signed(2), 0 ), Error::<Test>::OfferNotExists );
+
+		assert_ok!( KittiesModule::make_offer( Origin::signed(2), 0, 1_000, 20 ) );
+		run_to_block(20);
+		assert_noop!( KittiesModule::accept_offer( Origin::signed(1), 0, 2 ), Error::<Test>::OfferExpired );
+
+		assert_ok!( KittiesModule::withdraw_offer( Origin::signed(2), 0 ) );
+		assert_eq!( Balances::reserved_balance(2), 0 );
+		assert_eq!( KittiesModule::offers(0, 2), None );
+		assert_eq!( KittiesModule::offer_count(0), 0 );
+		assert_noop!( KittiesModule::accept_offer( Origin::signed(1), 0, 2 ), Error::<Test>::OfferNotExists );
+	});
+}
+
+// 每只猫的报价数量有上限（Mock 里边是 2），替换自己之前的报价不算新的报价
+#[test]
+fn make_offer_failed_when_too_many(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+
+		assert_ok!( KittiesModule::make_offer( Origin::signed(2), 0, 1_000, 20 ) );
+		assert_ok!( KittiesModule::make_offer( Origin::signed(3), 0, 1_000, 20 ) );
+		assert_eq!( KittiesModule::offer_count(0), 2 );
+		assert_noop!( KittiesModule::make_offer( Origin::signed(4), 0, 1_000, 20 ), Error::<Test>::TooManyOffers );
+		assert_ok!( KittiesModule::make_offer( Origin::signed(3), 0, 2_000, 20 ) );
+		assert_eq!( KittiesModule::offer_count(0), 2 );
+
+		assert_ok!( KittiesModule::accept_offer( Origin::signed(1), 0, 3 ) );
+		assert_eq!( KittiesModule::offer_count(0), 1 );
+		assert_ok!( KittiesModule::make_offer( Origin::signed(4), 0, 1_000, 20 ) );
+	});
+}
+
+// 过期的报价任何人都可以清理，质押退还给报价的人，空出来的报价数量可以给别人用
+#[test]
+fn clear_expired_offer_work(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		assert_ok!( KittiesModule::make_offer( Origin::signed(2), 0, 1_000, 20 ) );
+		assert_ok!( KittiesModule::make_offer( Origin::signed(3), 0, 1_000, 20 ) );
+
+		assert_noop!( KittiesModule::clear_expired_offer( Origin::signed(4), 0, 2 ), Error::<Test>::OfferNotExpired );
+		assert_noop!( KittiesModule::clear_expired_offer( Origin::signed(4), 0, 5 ), Error::<Test>::OfferNotExists );
+
+		run_to_block(20);
+		assert_ok!( KittiesModule::clear_expired_offer( Origin::signed(4), 0, 2 ) );
+		assert_eq!( KittiesModule::offers(0, 2), None );
+		assert_eq!( KittiesModule::offer_count(0), 1 );
+		assert_eq!( Balances::reserved_balance(2), 0 );
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_kitties( RawEvent::OfferWithdrawn( 2u64, 0 ) )
+		);
+		assert_ok!( KittiesModule::make_offer( Origin::signed(4), 0, 1_000, 30 ) );
+	});
+}
+
+// 买家的质押不够付出价的时候不能接受报价，猫和报价都保持不变
+#[test]
+fn accept_offer_failed_when_reserve_slashed(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		assert_ok!( KittiesModule::make_offer( Origin::signed(2), 0, 1_000, 20 ) );
+		let _ = Balances::slash_reserved( &2, 5_500 );
+
+		assert_noop!( KittiesModule::accept_offer( Origin::signed(1), 0, 2 ), Error::<Test>::MoneyNotEnough );
+		assert_eq!( KittiesModule::kitty_owners(0), Some(1) );
+		assert!( KittiesModule::offers(0, 2).is_some() );
+	});
+}
+
+// 购买之后拥有者索引也要跟着转移
+#[test]
+fn buy_kitty_moves_owned_index(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		assert_ok!( KittiesModule::ask( Origin::signed(1), 0, Some(1_000) ) );
+		assert_ok!( KittiesModule::buy( Origin::signed(2), 0, 1_000 ) );
+
+		assert_eq!( KittiesModule::kitty_owners(0), Some(2) );
+		assert_eq!( KittiesModule::owned_kitties(2, 0), Some(0) );
+		assert_eq!( KittiesModule::owned_kitties(1, 0), None );
+		assert_eq!( KittiesModule::kitty_prices(0), None );
+		assert!( KittiesModule::check_invariants().is_empty() );
+	});
+}
+
+// 存储不一致的时候，检查会报告每一处不一致
+#[test]
+fn check_invariants_reports_mismatch(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		assert!( KittiesModule::check_invariants().is_empty() );
+
+		OwnedKitties::<Test>::remove(1, 0);
+		OwnedKitties::<Test>::insert(2, 0, 0);
+		Balances::unreserve(&1, 5_000);
+
+		assert_eq!(
+			KittiesModule::check_invariants(),
+			vec![
+				InvariantViolation::MissingOwnedEntry(1, 0),
+				InvariantViolation::StaleOwnedEntry(2, 0),
+				InvariantViolation::InsufficientReserve(1),
+			]
+		);
+	});
+}
+
+// 升级的时候按照 KittyOwners 重建 OwnedKitties
+#[test]
+fn runtime_upgrade_rebuilds_owned_kitties(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		let _ = KittiesModule::create( Origin::signed(2) );
+		assert_eq!( StorageVersion::get(), Releases::V6_0_0 );
+
+		// 模拟旧版本 buy 留下的错误索引
+		StorageVersion::put(Releases::V1_0_0);
+		OwnedKitties::<Test>::remove(2, 1);
+		OwnedKitties::<Test>::insert(1, 1, 1);
+
+		KittiesModule::on_runtime_upgrade();
+
+		assert_eq!( StorageVersion::get(), Releases::V6_0_0 );
+		assert_eq!( KittiesModule::owned_kitties(1, 0), Some(0) );
+		assert_eq!( KittiesModule::owned_kitties(2, 1), Some(1) );
+		assert_eq!( KittiesModule::owned_kitties(1, 1), None );
+		assert!( KittiesModule::check_invariants().is_empty() );
+	});
+}
+
+// 升级的时候给旧的猫补上押金记录
+#[test]
+fn runtime_upgrade_records_kitty_deposits(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		let _ = KittiesModule::create( Origin::signed(2) );
+
+		// 模拟 V2_0_0 的时候没有押金记录
+		StorageVersion::put(Releases::V2_0_0);
+		KittyDeposits::<Test>::remove(0);
+		KittyDeposits::<Test>::remove(1);
+
+		KittiesModule::on_runtime_upgrade();
+
+		assert_eq!( StorageVersion::get(), Releases::V6_0_0 );
+		assert_eq!( KittiesModule::kitty_deposits(0), Some((1, 5_000)) );
+		assert_eq!( KittiesModule::kitty_deposits(1), Some((2, 5_000)) );
+		assert!( KittiesModule::check_invariants().is_empty() );
+	});
+}
+
+// 升级的时候按照家族关系补上代数
+#[test]
+fn runtime_upgrade_records_generations(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		let _ = KittiesModule::create( Origin::signed(1) );
+		set_gender(0, Gender::Male);
+		set_gender(1, Gender::Female);
+		assert_ok!( KittiesModule::breed( Origin::signed(1), 0, 1 ) );
+		let _ = KittiesModule::create( Origin::signed(1) );
+		set_gender(2, Gender::Male);
+		set_gender(3, Gender::Female);
+		assert_ok!( KittiesModule::breed( Origin::signed(1), 2, 3 ) );
+
+		// 模拟 V3_0_0 的时候没有代数的记录
+		StorageVersion::put(Releases::V3_0_0);
+		for kitty_id in 0..5 {
+			KittyGenerations::<Test>::remove(kitty_id);
+		}
+
+		KittiesModule::on_runtime_upgrade();
+
+		assert_eq!( StorageVersion::get(), Releases::V6_0_0 );
+		assert_eq!( KittiesModule::kitty_generations(0), 0 );
+		assert_eq!( KittiesModule::kitty_generations(2), 1 );
+		assert_eq!( KittiesModule::kitty_generations(3), 0 );
+		assert_eq!( KittiesModule::kitty_generations(4), 2 );
+	});
+}
+
+// 升级的时候按照孩子的数量补上繁殖次数
+#[test]
+fn runtime_upgrade_records_litters(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		let _ = KittiesModule::create( Origin::signed(1) );
+		set_gender(0, Gender::Male);
+		set_gender(1, Gender::Female);
+		assert_ok!( KittiesModule::breed( Origin::signed(1), 0, 1 ) );
+		let _ = KittiesModule::create( Origin::signed(1) );
+		set_gender(2, Gender::Male);
+		set_gender(3, Gender::Female);
+		assert_ok!( KittiesModule::breed( Origin::signed(1), 2, 3 ) );
+
+		// 模拟 V5_0_0 的时候没有繁殖次数的记录
+		StorageVersion::put(Releases::V5_0_0);
+		for kitty_id in 0..5 {
+			KittyLitters::<Test>::remove(kitty_id);
+		}
+
+		KittiesModule::on_runtime_upgrade();
+
+		assert_eq!( StorageVersion::get(), Releases::V6_0_0 );
+		assert_eq!( KittiesModule::kitty_litters(0), 1 );
+		assert_eq!( KittiesModule::kitty_litters(1), 1 );
+		assert_eq!( KittiesModule::kitty_litters(2), 1 );
+		assert_eq!( KittiesModule::kitty_litters(3), 1 );
+		assert_eq!( KittiesModule::kitty_litters(4), 0 );
+	});
+}
+
+// 升级的时候按照已有的报价补上每只猫的报价数量
+#[test]
+fn runtime_upgrade_records_offer_counts(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		let _ = KittiesModule::create( Origin::signed(1) );
+		assert_ok!( KittiesModule::make_offer( Origin::signed(2), 0, 1_000, 20 ) );
+		assert_ok!( KittiesModule::make_offer( Origin::signed(3), 0, 1_000, 20 ) );
+		assert_ok!( KittiesModule::make_offer( Origin::signed(2), 1, 1_000, 20 ) );
+
+		// 模拟 V4_0_0 的时候没有报价数量的记录
+		StorageVersion::put(Releases::V4_0_0);
+		OfferCount::<Test>::remove(0);
+		OfferCount::<Test>::remove(1);
+
+		KittiesModule::on_runtime_upgrade();
+
+		assert_eq!( StorageVersion::get(), Releases::V6_0_0 );
+		assert_eq!( KittiesModule::offer_count(0), 2 );
+		assert_eq!( KittiesModule::offer_count(1), 1 );
+	});
+}
+
+// 押金修改之后，转让的时候按照记录的押金解质押，新的拥有者按照新的押金质押
+#[test]
+fn transfer_unreserves_recorded_deposit(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		assert_eq!( KittiesModule::kitty_deposits(0), Some((1, 5_000)) );
+
+		NewKittyReserve::set(8_000);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		assert_eq!( Balances::reserved_balance(1), 13_000 );
+
+		assert_ok!( KittiesModule::transfer( Origin::signed(1), 2, 0 ) );
+		assert_ok!( KittiesModule::accept_gift( Origin::signed(2), 0 ) );
+		assert_eq!( Balances::reserved_balance(1), 8_000 );
+		assert_eq!( Balances::reserved_balance(2), 8_000 );
+		assert_eq!( KittiesModule::kitty_deposits(0), Some((2, 8_000)) );
+		assert!( KittiesModule::check_invariants().is_empty() );
+	});
+}
+
+// 只有治理可以调整押金，押金多了退还差额，少了补质押差额，余额不够的跳过
+#[test]
+fn rebase_deposits_work(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		let _ = KittiesModule::create( Origin::signed(2) );
+
+		NewKittyReserve::set(3_000);
+		assert_noop!( KittiesModule::rebase_deposits( Origin::signed(1), vec![0, 1] ), DispatchError::BadOrigin );
+		assert_ok!( KittiesModule::rebase_deposits( Origin::root(), vec![0] ) );
+		assert_eq!( KittiesModule::kitty_deposits(0), Some((1, 3_000)) );
+		assert_eq!( Balances::reserved_balance(1), 3_000 );
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_kitties( RawEvent::DepositRebased( 0, 1u64, 5_000, 3_000 ) )
+		);
+
+		// 账号 2 的余额不够补质押，保持原来的押金
+		NewKittyReserve::set(200_000_000);
+		assert_ok!( KittiesModule::rebase_deposits( Origin::root(), vec![0, 1, 2] ) );
+		assert_eq!( KittiesModule::kitty_deposits(0), Some((1, 200_000_000)) );
+		assert_eq!( Balances::reserved_balance(1), 200_000_000 );
+		assert_eq!( KittiesModule::kitty_deposits(1), Some((2, 5_000)) );
+		assert_eq!( Balances::reserved_balance(2), 5_000 );
+		assert!( KittiesModule::check_invariants().is_empty() );
+	});
+}
+
+// 释放猫：退还押金和报价，家族关系保留下来并记录为已经释放
+#[test]
+fn release_kitty_work(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		let _ = KittiesModule::create( Origin::signed(1) );
+		set_gender(0, Gender::Male);
+		set_gender(1, Gender::Female);
+		assert_ok!( KittiesModule::breed( Origin::signed(1), 0, 1 ) );
+		assert_ok!( KittiesModule::ask( Origin::signed(1), 2, Some(1_000) ) );
+		assert_ok!( KittiesModule::make_offer( Origin::signed(2), 2, 1_000, 20 ) );
+
+		assert_noop!( KittiesModule::release( Origin::signed(2), 2 ), Error::<Test>::NotKittyOwner );
+		assert_ok!( KittiesModule::release( Origin::signed(1), 2 ) );
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_kitties( RawEvent::Released( 1u64, 2, 5_000 ) )
+		);
+
+		assert!( KittiesModule::kitties(2).is_none() );
+		assert_eq!( KittiesModule::kitty_owners(2), None );
+		assert_eq!( KittiesModule::owned_kitties(1, 2), None );
+		assert_eq!( KittiesModule::kitty_prices(2), None );
+		assert_eq!( KittiesModule::kitty_deposits(2), None );
+		assert_eq!( KittiesModule::offers(2, 2), None );
+		assert_eq!( KittiesModule::offer_count(2), 0 );
+		assert_eq!( Balances::reserved_balance(1), 10_000 );
+		assert_eq!( Balances::reserved_balance(2), 0 );
+
+		// 家族关系保留下来
+		assert_eq!( KittiesModule::dead_kitties(2), Some(10) );
+		assert_eq!( KittiesModule::kitty_parents(2), Some((0, 1)) );
+		assert_eq!( KittiesModule::kitty_children(0, 2), Some(2) );
+		assert!( KittiesModule::check_invariants().is_empty() );
+
+		assert_noop!( KittiesModule::release( Origin::signed(1), 2 ), Error::<Test>::KittyNotExists );
+	});
+}
+
+// 测试繁殖失败，因为两只猫性别相同
+#[test]
+fn breed_kitty_fail_when_same_gender(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		let _ = KittiesModule::create( Origin::signed(1) );
+		set_gender(0, Gender::Female);
+		set_gender(1, Gender::Female);
+
+		assert_noop!( KittiesModule::breed( Origin::signed(1), 0, 1 ) , Error::<Test>::RequiredMaleAndFemale);
+	})
+}
+
+// 繁殖出来的猫比父母中代数大的那只多一代
+#[test]
+fn breed_kitty_increases_generation(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		let _ = KittiesModule::create( Origin::signed(1) );
+		set_gender(0, Gender::Male);
+		set_gender(1, Gender::Female);
+		assert_eq!( KittiesModule::kitty_generations(0), 0 );
+
+		assert_ok!( KittiesModule::breed( Origin::signed(1), 0, 1 ) );
+		assert_eq!( KittiesModule::kitty_generations(2), 1 );
+
+		set_gender(2, Gender::Female);
+		// 第 0 代的猫繁殖一次之后休息 5 × (1 + 0 + 1) 个区块
+		run_to_block(20);
+		assert_ok!( KittiesModule::breed( Origin::signed(1), 0, 2 ) );
+		assert_eq!( KittiesModule::kitty_generations(3), 2 );
+		assert_eq!( KittiesModule::kitty_info(3).map(|info| info.generation), Some(2) );
+	})
+}
+
+// 按照 DNA 的约定解析性别、稀有度和外观特征
+#[test]
+fn kitty_info_decodes_dna(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		let dna = [1, 250, 0, 17, 34, 51, 68, 255, 0, 0, 0, 0, 0, 0, 0, 0];
+		Kitties::<Test>::insert(0, Kitty(dna));
+
+		assert_eq!(
+			KittiesModule::kitty_info(0),
+			Some(KittyInfo {
+				dna,
+				gender: Gender::Female,
+				generation: 0,
+				rarity: Rarity::Epic,
+				traits: KittyTraits { fur_colour: 0, pattern: 1, eye_colour: 2, eye_shape: 3, mouth: 4, tail: 15 },
+			})
+		);
+		assert_eq!( KittiesModule::kitty_info(1), None );
+	})
+}
+
+// 没有突变的时候，每个基因都来自父母中的一个，性别由随机数决定
+#[test]
+fn breed_dna_inherits_from_parents(){
+	let dna1 = [0u8; 16];
+	let dna2 = [0xffu8; 16];
+	let mut selector = [0x80u8; 16];
+	selector[0] = 3;
+	selector[2] = 0x81;
+
+	let child = genetics::breed_dna(&dna1, &dna2, &selector);
+	assert_eq!( Kitty(child).gender(), Gender::Female );
+	assert_eq!( child[1], 0 );
+	assert_eq!( child[2], 0xff );
+	assert!( child[3..].iter().all(|gene| *gene == 0) );
+}
+
+// 繁殖之后父母需要休息，休息时间随着代数和繁殖次数增加，并且有最多的繁殖次数
+#[test]
+fn breed_kitty_cooldown_and_litter_limit(){
+	new_test_ext().execute_with(|| {
+		run_to_block(10);
+		let _ = KittiesModule::create( Origin::signed(1) );
+		let _ = KittiesModule::create( Origin::signed(1) );
+		set_gender(0, Gender::Male);
+		set_gender(1, Gender::Female);
+
+		assert_ok!( KittiesModule::breed( Origin::signed(1), 0, 1 ) );
+		assert_eq!( KittiesModule::kitty_litters(0), 1 );
+		assert_eq!( KittiesModule::kitty_cooldowns(0), 20 );
+		assert_noop!( KittiesModule::breed( Origin::signed(1), 0, 1 ), Error::<Test>::KittyTired );
+
+		run_to_block(20);
+		assert_ok!( KittiesModule::breed( Origin::signed(1), 0, 1 ) );
+		// 第二次繁殖之后休息 5 × (1 + 0 + 2) 个区块
+		assert_eq!( KittiesModule::kitty_cooldowns(1), 35 );
+
+		run_to_block(35);
+		assert_noop!( KittiesModule::breed( Origin::signed(1), 0, 1 ), Error::<Test>::TooManyLitters );
+
+		// 第 1 代的猫休息时间更长
+		set_gender(2, Gender::Male);
+		set_gender(3, Gender::Female);
+		assert_ok!( KittiesModule::breed( Origin::signed(1), 2, 3 ) );
+		assert_eq!( KittiesModule::kitty_cooldowns(2), 35 + 15 );
+	})
+}
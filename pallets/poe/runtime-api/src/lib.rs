@@ -0,0 +1,36 @@
+//! Runtime API definition for the POE pallet.
+//! 链下（例如后端服务）可以通过这些接口直接查询存证，不需要了解存证在链上的存储结构。
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+pub use pallet_poe::{BatchAnchor, ClaimDigest, DigestAlgorithm, Proof, ProvenanceRecord, Tombstone};
+
+sp_api::decl_runtime_apis! {
+	pub trait PoeApi<AccountId, BlockNumber, Moment, Balance> where
+		AccountId: Codec,
+		BlockNumber: Codec,
+		Moment: Codec,
+		Balance: Codec,
+	{
+		/// 根据文件摘要查询存证，返回拥有者、存证区块、时间戳和押金，存证不存在时返回 None
+		fn verify_claim(claim: ClaimDigest) -> Option<Proof<AccountId, BlockNumber, Moment, Balance>>;
+		/// 分页查询某个账号拥有的存证，page 从 0 开始
+		fn claims_of(owner: AccountId, page: u32, page_size: u32) -> Vec<ClaimDigest>;
+		/// 查询存证完整的流转记录，第一条是创建者，之后按时间顺序是每一次转让后的拥有者
+		fn claim_history(claim: ClaimDigest) -> Vec<ProvenanceRecord<AccountId, BlockNumber, Moment>>;
+		/// 查询为存证背书的公证人以及背书的区块
+		fn endorsements_of(claim: ClaimDigest) -> Vec<(AccountId, BlockNumber)>;
+		/// 用默克尔路径验证文件哈希属于 owner 提交的某个批量存证，验证通过返回批量存证的信息，否则返回 None
+		fn verify_batch_membership(
+			owner: AccountId,
+			root: [u8; 32],
+			leaf: [u8; 32],
+			index: u32,
+			path: Vec<[u8; 32]>,
+		) -> Option<BatchAnchor<AccountId, BlockNumber, Moment, Balance>>;
+		/// 查询被删除的存证留下的墓碑，没有被删除过返回 None
+		fn tombstone_of(claim: ClaimDigest) -> Option<Tombstone<AccountId, BlockNumber>>;
+	}
+}
@@ -0,0 +1,165 @@
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{benchmarks, account};
+use frame_system::RawOrigin;
+use sp_runtime::{traits::Bounded, KeyTypeId, MultiSigner, MultiSignature};
+use sp_std::prelude::*;
+use crate::Module as PoeModule;
+
+const SEED: u32 = 0;
+
+// 代为提交存证的基准测试需要拥有者的签名，不同的 runtime 公钥和签名的类型不一样，由这个 trait 生成
+pub trait BenchmarkSigner<Signature>: Sized {
+	fn sign(payload: &[u8]) -> (Self, Signature);
+}
+
+// runtime 里边用的是 MultiSigner，用 sr25519 生成一对新的密钥签名
+impl BenchmarkSigner<MultiSignature> for MultiSigner {
+	fn sign(payload: &[u8]) -> (Self, MultiSignature) {
+		let key_type = KeyTypeId(*b"poe_");
+		let public = sp_io::crypto::sr25519_generate(key_type, None);
+		let signature = sp_io::crypto::sr25519_sign(key_type, &public, payload)
+			.expect("the key was just generated; qed");
+		(public.into(), signature.into())
+	}
+}
+
+// 返回一个有足够多钱的账号，两个账号都用最大余额的话总发行量会溢出，所以只给四分之一
+fn funded_account<T: Config>(name: &'static str, index: u32) -> T::AccountId {
+	let caller: T::AccountId = account(name, index, SEED);
+	T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 4u32.into());
+	caller
+}
+
+// 构造长度为 l 的元数据，依次填满标题、文件类型和链下地址
+fn metadata_of_len<T: Config>(l: u32) -> ClaimMetadata {
+	let title_len = l.min(T::MaxTitleLength::get());
+	let content_type_len = (l - title_len).min(T::MaxContentTypeLength::get());
+	let uri_len = l - title_len - content_type_len;
+	ClaimMetadata {
+		title: vec![b't'; title_len as usize],
+		content_type: vec![b'c'; content_type_len as usize],
+		size: 0,
+		uri: if uri_len > 0 { Some(vec![b'u'; uri_len as usize]) } else { None },
+	}
+}
+
+fn claim_digest() -> ClaimDigest {
+	ClaimDigest { algorithm: DigestAlgorithm::Blake2_256, hash: [1u8; 32], namespace: None }
+}
+
+benchmarks! {
+	where_clause { where T::Public: BenchmarkSigner<T::Signature> }
+
+	create_claim {
+		let l in 0 .. PoeModule::<T>::max_metadata_len();
+		let caller = funded_account::<T>("caller", 0);
+		let claim = claim_digest();
+	}: _(RawOrigin::Signed(caller), claim, metadata_of_len::<T>(l), None)
+	verify {
+		assert!(Proofs::<T>::contains_key(&claim));
+	}
+
+	create_claim_for {
+		let l in 0 .. PoeModule::<T>::max_metadata_len();
+		let relayer = funded_account::<T>("relayer", 0);
+		let claim = claim_digest();
+		let metadata = metadata_of_len::<T>(l);
+		let payload = PoeModule::<T>::relayed_claim_payload(&claim, &metadata, &None, 0);
+		let (signer, signature) = <T::Public as BenchmarkSigner<T::Signature>>::sign(&payload);
+		let owner = signer.clone().into_account();
+	}: _(RawOrigin::Signed(relayer), claim, metadata, None, signer, 0, signature)
+	verify {
+		assert_eq!(Proofs::<T>::get(&claim).map(|proof| proof.owner), Some(owner));
+	}
+
+	revoke_claim {
+		let l in 0 .. PoeModule::<T>::max_metadata_len();
+		let e in 0 .. T::MaxEndorsementsPerClaim::get();
+		let caller = funded_account::<T>("caller", 0);
+		let claim = claim_digest();
+		PoeModule::<T>::do_create_claim(caller.clone(), caller.clone(), claim, metadata_of_len::<T>(l), None)?;
+		for i in 0 .. e {
+			let notary: T::AccountId = account("notary", i, SEED);
+			Notaries::<T>::insert(&notary, T::BlockNumber::from(1u32));
+			PoeModule::<T>::endorse_claim(RawOrigin::Signed(notary).into(), claim)?;
+		}
+	}: _(RawOrigin::Signed(caller), claim, Some(0))
+	verify {
+		assert!(!Proofs::<T>::contains_key(&claim));
+		assert!(Tombstones::<T>::contains_key(&claim));
+		assert_eq!(EndorsementCount::get(&claim), 0);
+	}
+
+	transfer_claim {
+		let l in 0 .. PoeModule::<T>::max_metadata_len();
+		let caller = funded_account::<T>("caller", 0);
+		let receiver = funded_account::<T>("receiver", 0);
+		let claim = claim_digest();
+		PoeModule::<T>::do_create_claim(caller.clone(), caller.clone(), claim, metadata_of_len::<T>(l), None)?;
+	}: _(RawOrigin::Signed(caller), claim, receiver)
+	verify {
+		assert!(PendingTransfers::<T>::contains_key(&claim));
+	}
+
+	accept_transfer {
+		let l in 0 .. PoeModule::<T>::max_metadata_len();
+		let caller = funded_account::<T>("caller", 0);
+		let receiver = funded_account::<T>("receiver", 0);
+		let claim = claim_digest();
+		PoeModule::<T>::do_create_claim(caller.clone(), caller.clone(), claim, metadata_of_len::<T>(l), None)?;
+		PoeModule::<T>::transfer_claim(RawOrigin::Signed(caller).into(), claim, receiver.clone())?;
+	}: _(RawOrigin::Signed(receiver.clone()), claim)
+	verify {
+		assert_eq!(Proofs::<T>::get(&claim).map(|proof| proof.owner), Some(receiver));
+	}
+
+	// 最坏的情况：代为提交的存证，押金从 relayer 转到拥有者身上
+	set_claim_metadata {
+		let l in 0 .. PoeModule::<T>::max_metadata_len();
+		let owner = funded_account::<T>("owner", 0);
+		let relayer = funded_account::<T>("relayer", 0);
+		let claim = claim_digest();
+		PoeModule::<T>::do_create_claim(owner.clone(), relayer, claim, ClaimMetadata::default(), None)?;
+	}: _(RawOrigin::Signed(owner.clone()), claim, metadata_of_len::<T>(l))
+	verify {
+		assert_eq!(Proofs::<T>::get(&claim).map(|proof| proof.depositor), Some(owner));
+	}
+
+	anchor_batch {
+		let caller = funded_account::<T>("caller", 0);
+	}: _(RawOrigin::Signed(caller.clone()), [1u8; 32], 1_000)
+	verify {
+		assert!(BatchAnchors::<T>::contains_key(&caller, &[1u8; 32]));
+	}
+
+	remove_batch {
+		let caller = funded_account::<T>("caller", 0);
+		PoeModule::<T>::anchor_batch(RawOrigin::Signed(caller.clone()).into(), [1u8; 32], 1_000)?;
+	}: _(RawOrigin::Signed(caller.clone()), [1u8; 32])
+	verify {
+		assert!(!BatchAnchors::<T>::contains_key(&caller, &[1u8; 32]));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{new_test_ext, Test};
+	use frame_support::assert_ok;
+
+	#[test]
+	fn test_benchmarks() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_create_claim::<Test>());
+			assert_ok!(test_benchmark_create_claim_for::<Test>());
+			assert_ok!(test_benchmark_revoke_claim::<Test>());
+			assert_ok!(test_benchmark_transfer_claim::<Test>());
+			assert_ok!(test_benchmark_accept_transfer::<Test>());
+			assert_ok!(test_benchmark_set_claim_metadata::<Test>());
+			assert_ok!(test_benchmark_anchor_batch::<Test>());
+			assert_ok!(test_benchmark_remove_batch::<Test>());
+		});
+	}
+}
@@ -4,10 +4,19 @@
 /// Learn more about FRAME and the core library of Substrate FRAME pallets:
 /// https://substrate.dev/docs/en/knowledgebase/runtime/frame
 
-use frame_support::{decl_module, decl_storage, decl_event, decl_error, dispatch, ensure,};
+use codec::{Encode, Decode};
+use frame_support::{decl_module, decl_storage, decl_event, decl_error, dispatch, ensure, transactional, RuntimeDebug, Parameter, weights::Weight};
+use sp_std::cmp::Ordering;
 use frame_system::{self as system, ensure_signed};
 use sp_std::prelude::*;
-use frame_support::traits::Get;
+use frame_support::traits::{Time, Get, Currency, ReservableCurrency, EnsureOrigin, ExistenceRequirement};
+use sp_runtime::traits::{Saturating, Verify, IdentifyAccount, Zero};
+use sp_io::hashing::blake2_256;
+
+mod benchmarking;
+pub mod migrations;
+pub mod weights;
+pub use weights::WeightInfo;
 
 #[cfg(test)]
 mod mock;
@@ -15,12 +24,199 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+// 存储结构的版本，修改存储结构的时候增加一个版本，并在 on_runtime_upgrade 里边迁移旧的数据
+// V1_0_0：Proofs 的 KEY 是存证的原始内容，值是 (拥有者, 区块) 元组
+// V2_0_0：Proofs 的 KEY 是 ClaimDigest，值是 Proof 结构
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Releases {
+	V1_0_0,
+	V2_0_0,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1_0_0
+	}
+}
+
+// 存证摘要所使用的哈希算法，链上只记录算法标识，哈希由链下计算
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum DigestAlgorithm {
+	Blake2_256,
+	Sha2_256,
+}
+
+// 存证的命名空间编号，注册命名空间的时候按顺序分配
+pub type NamespaceId = u32;
+
+// 存证的摘要：算法标识 + 32 字节的哈希值，文件本身不需要上链
+// namespace 为 None 表示全局的存证，不同命名空间里边可以有相同的哈希
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct ClaimDigest {
+	pub algorithm: DigestAlgorithm,
+	pub hash: [u8; 32],
+	pub namespace: Option<NamespaceId>,
+}
+
+// 命名空间：拥有者和注册时质押的押金
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Namespace<AccountId, Balance> {
+	pub owner: AccountId,
+	pub deposit: Balance,
+}
+
+// 存证的元数据：标题、文件类型（MIME）、原始文件的字节数、可选的链下地址，长度都在 Config 里边限制
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, Default)]
+pub struct ClaimMetadata {
+	pub title: Vec<u8>,
+	pub content_type: Vec<u8>,
+	pub size: u64,
+	pub uri: Option<Vec<u8>>,
+}
+
+// 存证的信息：拥有者、存证所在的区块、存证时的时间戳、押金以及质押押金的账号、元数据、过期的区块（None 表示永久有效）
+// 一般是拥有者自己质押押金，代为提交的存证由提交人（relayer）质押
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Proof<AccountId, BlockNumber, Moment, Balance> {
+	pub owner: AccountId,
+	pub depositor: AccountId,
+	pub block_number: BlockNumber,
+	pub timestamp: Moment,
+	pub deposit: Balance,
+	pub metadata: ClaimMetadata,
+	pub expires_at: Option<BlockNumber>,
+}
+
+// 存证的一条流转记录：谁在哪个区块、什么时间成为了存证的拥有者，第一条记录就是创建者
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ProvenanceRecord<AccountId, BlockNumber, Moment> {
+	pub owner: AccountId,
+	pub block_number: BlockNumber,
+	pub timestamp: Moment,
+}
+
+// 等待接收人确认的存证转让：接收人、在哪个区块过期
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct PendingTransfer<AccountId, BlockNumber> {
+	pub receiver: AccountId,
+	pub expires_at: BlockNumber,
+}
+
+// 批量存证：一批文件哈希组成的默克尔树的树根，只上链树根和叶子数量，以及质押的押金
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct BatchAnchor<AccountId, BlockNumber, Moment, Balance> {
+	pub owner: AccountId,
+	pub leaf_count: u32,
+	pub block_number: BlockNumber,
+	pub timestamp: Moment,
+	pub deposit: Balance,
+}
+
+// 多人共有的存证：共有人名单（包括存证的拥有者）和执行操作需要的同意人数
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct JointOwnership<AccountId> {
+	pub owners: Vec<AccountId>,
+	pub threshold: u32,
+}
+
+// 多人共有的存证需要共有人同意才能执行的操作
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum JointAction<AccountId> {
+	// 删除存证
+	Revoke,
+	// 发起存证转让，接收人确认之后才会真正转移
+	Transfer(AccountId),
+}
+
+// 等待共有人同意的操作，以及已经同意的共有人
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct JointProposal<AccountId> {
+	pub action: JointAction<AccountId>,
+	pub approvals: Vec<AccountId>,
+}
+
+// 被删除的存证留下的墓碑：删除人、删除的区块和可选的原因代码，有墓碑的存证不能再次创建
+// 删除人为 None 表示是仲裁裁决删除的
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Tombstone<AccountId, BlockNumber> {
+	pub revoker: Option<AccountId>,
+	pub block_number: BlockNumber,
+	pub reason: Option<u32>,
+}
+
+// 第三方对存证发起的异议，发起人需要质押押金，等待仲裁裁决
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Dispute<AccountId, BlockNumber, Balance> {
+	pub challenger: AccountId,
+	pub block_number: BlockNumber,
+	pub reason: Option<u32>,
+	pub deposit: Balance,
+}
+
+// 仲裁对异议的裁决
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum DisputeRuling<AccountId> {
+	// 驳回异议，异议人的押金被罚没
+	Dismiss,
+	// 冻结存证，拥有者不能再删除、转让或者修改
+	Freeze,
+	// 把存证判给指定的账号
+	Reassign(AccountId),
+	// 删除存证并留下墓碑
+	Revoke,
+}
+
+pub type MomentOf<T> = <<T as Config>::Time as Time>::Moment;
+pub type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+pub type ProofOf<T> = Proof<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber, MomentOf<T>, BalanceOf<T>>;
+pub type PendingTransferOf<T> = PendingTransfer<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber>;
+pub type BatchAnchorOf<T> = BatchAnchor<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber, MomentOf<T>, BalanceOf<T>>;
+pub type ProvenanceRecordOf<T> = ProvenanceRecord<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber, MomentOf<T>>;
+pub type TombstoneOf<T> = Tombstone<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber>;
+pub type NamespaceOf<T> = Namespace<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+pub type DisputeOf<T> = Dispute<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber, BalanceOf<T>>;
+
 /// Configure the pallet by specifying the parameters and types on which it depends.
 pub trait Config: frame_system::Config {
 	// 因为需要触发时间，所以需要有这一行
 	type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
-	// 设置存证最大长度，这样设置，可以在 runtime 里边去配置具体长度
-	type MaxClaimLength: Get<u32>;
+	// 存证时间的来源，在 runtime 里边配置为 pallet_timestamp
+	type Time: Time;
+	// Currency 类型，用于质押存证的押金
+	type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
+	// 每个存证都需要质押的基础押金
+	type ClaimDepositBase: Get<BalanceOf<Self>>;
+	// 存证在链上每占用一个字节，需要额外质押的押金
+	type ClaimDepositPerByte: Get<BalanceOf<Self>>;
+	// 元数据各个字段的最大长度，这样设置，可以在 runtime 里边去配置具体长度
+	type MaxTitleLength: Get<u32>;
+	type MaxContentTypeLength: Get<u32>;
+	type MaxUriLength: Get<u32>;
+	// 每个账号最多能拥有的存证数量
+	type MaxClaimsPerAccount: Get<u32>;
+	// 每个存证最多保存的流转记录数（包括创建的那一条），满了之后就不能再转让
+	type MaxHistoryLength: Get<u32>;
+	// 存证转让发起后，接收人需要在多少个区块内确认，超时自动失效
+	type TransferOfferTimeout: Get<Self::BlockNumber>;
+	// 同一个区块最多能有多少个存证过期，用来限制 on_initialize 里边清理过期存证的工作量
+	type MaxExpiriesPerBlock: Get<u32>;
+	// 管理公证人名单的权限，在 runtime 里边可以配置为 root 或者议会
+	type NotaryOrigin: EnsureOrigin<Self::Origin>;
+	// 多人共有的存证最多能有多少个共有人
+	type MaxCoOwners: Get<u32>;
+	// 每个存证最多能有多少个公证人背书，用来限制删除存证的时候清理背书的工作量
+	type MaxEndorsementsPerClaim: Get<u32>;
+	// 对异议做出裁决的权限，在 runtime 里边可以配置为 root 或者议会
+	type DisputeOrigin: EnsureOrigin<Self::Origin>;
+	// 发起异议需要质押的押金，异议被驳回的时候罚没
+	type DisputeDeposit: Get<BalanceOf<Self>>;
+	// 代为提交存证时，存证拥有者的公钥和签名的类型，在 runtime 里边配置为 MultiSigner 和 MultiSignature，支持 sr25519 和 ed25519
+	type Public: IdentifyAccount<AccountId = Self::AccountId> + Parameter;
+	type Signature: Verify<Signer = Self::Public> + Parameter;
+	// 注册命名空间需要质押的押金
+	type NamespaceDeposit: Get<BalanceOf<Self>>;
+	// 各个交易的权重，由 benchmark 生成
+	type WeightInfo: WeightInfo;
 }
 
 // The pallet's runtime storage items.
@@ -30,21 +226,134 @@ decl_storage! {
 	// This name may be updated, but each pallet in the runtime must use a unique name.
 	// ---------------------------------vvvvvvvvvvvvvv
 	trait Store for Module<T: Config> as PoeModule {
-		// 存证保存的变量，十一二点 hashmap ，KEY 是存证内容的，会用 blake2_128_concat 就行 HASH ，只是一个元组
-		Proofs get(fn proofs): map hasher(blake2_128_concat) Vec<u8> => (T::AccountId, T::BlockNumber);
+		// 存储结构的版本，新链直接是最新的版本，旧链没有这个值，默认是 V1_0_0
+		StorageVersion build(|_: &GenesisConfig| Releases::V2_0_0): Releases;
+		// 存证保存的变量，是一个 hashmap ，KEY 是存证的摘要，会用 blake2_128_concat 进行 HASH
+		Proofs get(fn proofs): map hasher(blake2_128_concat) ClaimDigest => Option<ProofOf<T>>;
+		// 记录某个拥有者与存证之间的关系，值也是存证的摘要，用于按拥有者查询存证
+		OwnedClaims get(fn owned_claims): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) ClaimDigest => Option<ClaimDigest>;
+		// 记录每个拥有者拥有的存证数量
+		OwnedClaimsCount get(fn owned_claims_count): map hasher(blake2_128_concat) T::AccountId => u32;
+		// 存证的流转记录，只追加不修改，按时间顺序保存
+		ClaimHistory get(fn claim_history): map hasher(blake2_128_concat) ClaimDigest => Vec<ProvenanceRecordOf<T>>;
+		// 等待接收人确认的存证转让
+		PendingTransfers get(fn pending_transfers): map hasher(blake2_128_concat) ClaimDigest => Option<PendingTransferOf<T>>;
+		// 记录每个区块过期的存证转让，第一个值是过期的区块，第二个值是存证，值也是存证，在 on_initialize 里边清理
+		TransferExpiries get(fn transfer_expiries): double_map hasher(twox_64_concat) T::BlockNumber, hasher(blake2_128_concat) ClaimDigest => Option<ClaimDigest>;
+		// 记录每个区块过期的存证，第一个值是过期的区块，第二个值是存证，值也是存证，在 on_initialize 里边清理
+		ClaimExpiries get(fn claim_expiries): double_map hasher(twox_64_concat) T::BlockNumber, hasher(blake2_128_concat) ClaimDigest => Option<ClaimDigest>;
+		// 记录每个区块过期的存证数量
+		ClaimExpiriesCount get(fn claim_expiries_count): map hasher(twox_64_concat) T::BlockNumber => u32;
+		// 多人共有的存证的共有人名单，单人拥有的存证没有这个数据
+		JointOwners get(fn joint_owners): map hasher(blake2_128_concat) ClaimDigest => Option<JointOwnership<T::AccountId>>;
+		// 多人共有的存证等待同意的操作，同一时间只有一个
+		JointProposals get(fn joint_proposals): map hasher(blake2_128_concat) ClaimDigest => Option<JointProposal<T::AccountId>>;
+		// 公证人名单，值是成为公证人的区块
+		Notaries get(fn notaries): map hasher(blake2_128_concat) T::AccountId => Option<T::BlockNumber>;
+		// 存证的背书，第一个值是存证，第二个值是背书的公证人，值是背书的区块
+		Endorsements get(fn endorsements): double_map hasher(blake2_128_concat) ClaimDigest, hasher(blake2_128_concat) T::AccountId => Option<T::BlockNumber>;
+		// 每个存证的背书数量，不会超过 MaxEndorsementsPerClaim
+		EndorsementCount get(fn endorsement_count): map hasher(blake2_128_concat) ClaimDigest => u32;
+		// 批量存证，第一个 KEY 是提交的账号，第二个是默克尔树的树根
+		// 同一个树根可以由不同的账号分别提交，别人抢先提交同一个树根也不会变成这个账号的批量存证
+		BatchAnchors get(fn batch_anchors): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) [u8; 32] => Option<BatchAnchorOf<T>>;
+		// 被删除的存证的墓碑，一直保留
+		Tombstones get(fn tombstones): map hasher(blake2_128_concat) ClaimDigest => Option<TombstoneOf<T>>;
+		// 等待仲裁的异议，每个存证同一时间只能有一个
+		Disputes get(fn disputes): map hasher(blake2_128_concat) ClaimDigest => Option<DisputeOf<T>>;
+		// 被仲裁冻结的存证以及冻结的区块
+		FrozenClaims get(fn frozen_claims): map hasher(blake2_128_concat) ClaimDigest => Option<T::BlockNumber>;
+		// 每个账号代为提交存证的下一个 nonce，用来防止签名被重放
+		Nonces get(fn nonces): map hasher(blake2_128_concat) T::AccountId => u64;
+		// 下一个注册的命名空间的编号
+		NextNamespaceId get(fn next_namespace_id): NamespaceId;
+		// 已经注册的命名空间
+		Namespaces get(fn namespaces): map hasher(twox_64_concat) NamespaceId => Option<NamespaceOf<T>>;
+		// 命名空间授权可以写入存证的成员，以及授权的区块，命名空间的拥有者不需要授权
+		NamespaceWriters get(fn namespace_writers): double_map hasher(twox_64_concat) NamespaceId, hasher(blake2_128_concat) T::AccountId => Option<T::BlockNumber>;
+		// 存证的拥有者授权可以代为转让这个存证的账号，拥有者变了之后清除
+		Approvals get(fn approvals): map hasher(blake2_128_concat) ClaimDigest => Option<T::AccountId>;
+		// 拥有者授权可以代为转让自己所有存证的账号，第一个值是拥有者，第二个值是被授权的账号
+		OperatorApprovals get(fn operator_approvals): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) T::AccountId => bool;
+		// 挂单出售的存证以及价格，转让或者删除之后清除
+		ClaimPrices get(fn claim_prices): map hasher(blake2_128_concat) ClaimDigest => Option<BalanceOf<T>>;
 	}
 }
 
 // Pallets use events to inform users when important changes are made.
 // https://substrate.dev/docs/en/knowledgebase/runtime/events
 decl_event!(
-	pub enum Event<T> where AccountId = <T as frame_system::Config>::AccountId {
+	pub enum Event<T> where
+		AccountId = <T as frame_system::Config>::AccountId,
+		Balance = BalanceOf<T>,
+		BlockNumber = <T as frame_system::Config>::BlockNumber,
+		Action = JointAction<<T as frame_system::Config>::AccountId>,
+		Ruling = DisputeRuling<<T as frame_system::Config>::AccountId>,
+	{
 		/// Event documentation should end with an array that provides descriptive names for event
 		/// parameters. [something, who]
-		// 触发的事件，包括：创建、删除、转让
-		ClaimCreated(AccountId, Vec<u8>),
-		ClaimRemoved(AccountId, Vec<u8>),
-		ClaimTransfered(AccountId, Vec<u8>, AccountId),
+		// 触发的事件，包括：创建、删除、转让，同时带上质押或者释放的押金数量
+		/// [owner, claim, reserved_deposit]
+		ClaimCreated(AccountId, ClaimDigest, Balance),
+		/// [owner, claim, released_deposit]
+		ClaimRemoved(AccountId, ClaimDigest, Balance),
+		/// [sender, claim, receiver, released_deposit, reserved_deposit]
+		ClaimTransfered(AccountId, ClaimDigest, AccountId, Balance, Balance),
+		/// [owner, claim, old_deposit, new_deposit]
+		ClaimMetadataUpdated(AccountId, ClaimDigest, Balance, Balance),
+		// 存证转让的各个步骤：发起、拒绝、取消、过期，接收人确认之后触发 ClaimTransfered
+		/// [owner, claim, receiver, expires_at]
+		ClaimTransferOffered(AccountId, ClaimDigest, AccountId, BlockNumber),
+		/// [receiver, claim]
+		ClaimTransferRejected(AccountId, ClaimDigest),
+		/// [owner, claim]
+		ClaimTransferCancelled(AccountId, ClaimDigest),
+		/// [claim, receiver]
+		ClaimTransferExpired(ClaimDigest, AccountId),
+		/// [owner, merkle_root, leaf_count]
+		BatchAnchored(AccountId, [u8; 32], u32),
+		/// [owner, merkle_root, released_deposit]
+		BatchRemoved(AccountId, [u8; 32], Balance),
+		/// [owner, claim, expires_at]
+		ClaimExtended(AccountId, ClaimDigest, Option<BlockNumber>),
+		/// [owner, claim, released_deposit]
+		ClaimExpired(AccountId, ClaimDigest, Balance),
+		/// [notary]
+		NotaryAdded(AccountId),
+		/// [notary]
+		NotaryRemoved(AccountId),
+		/// [notary, claim]
+		ClaimEndorsed(AccountId, ClaimDigest),
+		/// [notary, claim]
+		EndorsementWithdrawn(AccountId, ClaimDigest),
+		/// [owner, claim, owners, threshold]
+		JointOwnersSet(AccountId, ClaimDigest, Vec<AccountId>, u32),
+		/// [co_owner, claim, action]
+		JointActionProposed(AccountId, ClaimDigest, Action),
+		/// [co_owner, claim, approvals]
+		JointActionApproved(AccountId, ClaimDigest, u32),
+		/// [co_owner, claim]
+		JointActionCancelled(AccountId, ClaimDigest),
+		/// [challenger, claim, reserved_deposit]
+		ClaimDisputed(AccountId, ClaimDigest, Balance),
+		/// [claim, ruling]
+		DisputeRuled(ClaimDigest, Ruling),
+		/// [claim]
+		ClaimUnfrozen(ClaimDigest),
+		/// [owner, namespace, reserved_deposit]
+		NamespaceRegistered(AccountId, NamespaceId, Balance),
+		/// [namespace, writer]
+		NamespaceWriterAdded(NamespaceId, AccountId),
+		/// [namespace, writer]
+		NamespaceWriterRemoved(NamespaceId, AccountId),
+		/// [owner, claim, approved]
+		ClaimApproved(AccountId, ClaimDigest, Option<AccountId>),
+		/// [owner, operator, approved]
+		ApprovalForAll(AccountId, AccountId, bool),
+		/// [owner, claim, price]
+		ClaimAsk(AccountId, ClaimDigest, Option<Balance>),
+		/// [seller, buyer, claim, price]
+		ClaimSold(AccountId, AccountId, ClaimDigest, Balance),
 	}
 );
 
@@ -52,11 +361,103 @@ decl_event!(
 
 decl_error! {
 	pub enum Error for Module<T: Config> {
-		// 包含的错误信息，包括：存证已经存在、存证不存在，不是存证的主人，存证太长
+		// 包含的错误信息，包括：存证已经存在、存证不存在，不是存证的主人，押金不够
 		ProofAlreadyExists,
 		ProofNotExists,
 		NotClaimOwner,
-		ProofTooLong,
+		MoneyNotEnough,
+		// 元数据的标题、文件类型、链下地址太长
+		TitleTooLong,
+		ContentTypeTooLong,
+		UriTooLong,
+		// 拥有的存证数量超过了限制
+		TooManyClaims,
+		// 存证的流转记录已经满了
+		HistoryFull,
+		// 不能转让给自己
+		TransferToSelf,
+		// 存证已经有一个等待确认的转让
+		TransferAlreadyPending,
+		// 存证没有等待确认的转让
+		NoPendingTransfer,
+		// 不是转让的接收人
+		NotTransferReceiver,
+		// 批量存证的树根已经存在
+		BatchAlreadyAnchored,
+		// 批量存证至少要有一个叶子
+		EmptyBatch,
+		// 批量存证不存在
+		BatchNotExists,
+		// 过期的区块必须在当前区块之后
+		ExpiryInPast,
+		// 延期之后的过期区块必须比原来的晚
+		ExpiryNotExtended,
+		// 这个区块过期的存证已经太多了
+		TooManyExpiries,
+		// 已经是公证人了
+		AlreadyNotary,
+		// 不是公证人
+		NotNotary,
+		// 已经为这个存证背书过了
+		AlreadyEndorsed,
+		// 没有为这个存证背书过
+		NotEndorsed,
+		// 存证的背书已经达到最多的数量
+		TooManyEndorsements,
+		// 存证已经是多人共有的了
+		AlreadyJointClaim,
+		// 不是多人共有的存证
+		NotJointClaim,
+		// 共有人太多了
+		TooManyCoOwners,
+		// 同意人数必须在 1 到共有人数量之间
+		InvalidThreshold,
+		// 不是存证的共有人
+		NotCoOwner,
+		// 多人共有的存证，删除和转让需要通过共有人同意
+		JointApprovalRequired,
+		// 已经有一个等待同意的操作了
+		ProposalAlreadyPending,
+		// 没有等待同意的操作
+		NoPendingProposal,
+		// 已经同意过了
+		AlreadyApproved,
+		// 存证已经被删除过，不能再次创建
+		ProofRevoked,
+		// 不能对自己的存证发起异议
+		CannotDisputeOwnClaim,
+		// 存证已经有一个等待仲裁的异议
+		DisputeAlreadyPending,
+		// 存证没有等待仲裁的异议
+		NoPendingDispute,
+		// 存证有等待仲裁的异议，裁决之前不能删除、转让或者修改
+		ClaimDisputed,
+		// 存证已经被仲裁冻结
+		ClaimFrozen,
+		// 存证没有被冻结
+		NotFrozen,
+		// 存证拥有者的签名验证不通过
+		BadSignature,
+		// nonce 和账号下一个 nonce 不一致
+		InvalidNonce,
+		// 命名空间不存在
+		NamespaceNotExists,
+		// 不是命名空间的拥有者
+		NotNamespaceOwner,
+		// 没有在这个命名空间里边写入存证的权限
+		NotNamespaceWriter,
+		// 已经是命名空间的成员了
+		AlreadyNamespaceWriter,
+		// 命名空间的编号已经用完了
+		NoAvailableNamespaceId,
+		// 不能授权给存证的拥有者自己
+		ApprovalToOwner,
+		// 存证没有挂单出售
+		NotForSale,
+		// 出价低于挂单的价格
+		PriceTooLow,
+		// 已经是存证的拥有者了
+		AlreadyOwned,
 	}
 }
 
@@ -71,62 +472,926 @@ decl_module! {
 		// Events must be initialized if they are used by the pallet.
 		fn deposit_event() = default;
 
-		// 创建存证
-		#[weight = 10_000]
-		pub fn create_claim(origin, claim: Vec<u8>) -> dispatch::DispatchResult {
+		// 元数据的长度限制，放到 metadata 里边，方便前端读取
+		const MaxTitleLength: u32 = T::MaxTitleLength::get();
+		const MaxContentTypeLength: u32 = T::MaxContentTypeLength::get();
+		const MaxUriLength: u32 = T::MaxUriLength::get();
+		const MaxClaimsPerAccount: u32 = T::MaxClaimsPerAccount::get();
+		const MaxHistoryLength: u32 = T::MaxHistoryLength::get();
+		const TransferOfferTimeout: T::BlockNumber = T::TransferOfferTimeout::get();
+		const MaxExpiriesPerBlock: u32 = T::MaxExpiriesPerBlock::get();
+		const MaxEndorsementsPerClaim: u32 = T::MaxEndorsementsPerClaim::get();
+		const DisputeDeposit: BalanceOf<T> = T::DisputeDeposit::get();
+		const NamespaceDeposit: BalanceOf<T> = T::NamespaceDeposit::get();
+
+		// 升级的时候把旧版本的存证迁移到新的存储结构
+		fn on_runtime_upgrade() -> Weight {
+			migrations::migrate_to_v2::<T>()
+		}
+
+		// 每个区块开始的时候，清理在这个区块过期的存证转让和存证
+		fn on_initialize(n: T::BlockNumber) -> Weight {
+			Self::expire_transfers(n).saturating_add(Self::expire_claims(n))
+		}
+
+		// 创建存证，权重按照元数据的长度计算
+		#[weight = T::WeightInfo::create_claim(Module::<T>::metadata_len(metadata))]
+		pub fn create_claim(origin, claim: ClaimDigest, metadata: ClaimMetadata, expires_at: Option<T::BlockNumber>) -> dispatch::DispatchResult {
 			// 验签+获得调用者
 			let sender = ensure_signed(origin)?;
-			Self::do_create_claim(sender, claim)?;
+			Self::do_create_claim(sender.clone(), sender, claim, metadata, expires_at)?;
 
 			Ok(().into())
 		}
-		// 存证删除
-		#[weight = 10_000]
-		pub fn revoke_claim(origin, claim: Vec<u8>) -> dispatch::DispatchResult {
+		// 代为提交存证：拥有者在链下对存证签名，由 relayer 提交交易并质押押金，存证的拥有者是签名的账号
+		// 签名的内容见 relayed_claim_payload，nonce 必须等于拥有者的下一个 nonce，用过之后加一
+		#[weight = T::WeightInfo::create_claim_for(Module::<T>::metadata_len(metadata))]
+		pub fn create_claim_for(
+			origin,
+			claim: ClaimDigest,
+			metadata: ClaimMetadata,
+			expires_at: Option<T::BlockNumber>,
+			signer: T::Public,
+			nonce: u64,
+			signature: T::Signature,
+		) -> dispatch::DispatchResult {
+			let relayer = ensure_signed(origin)?;
+			let owner = signer.into_account();
+			ensure!(nonce == Self::nonces(&owner), Error::<T>::InvalidNonce);
+			let payload = Self::relayed_claim_payload(&claim, &metadata, &expires_at, nonce);
+			ensure!(signature.verify(&payload[..], &owner), Error::<T>::BadSignature);
+
+			Self::do_create_claim(owner.clone(), relayer, claim, metadata, expires_at)?;
+			Nonces::<T>::insert(&owner, nonce.saturating_add(1));
+			Ok(())
+		}
+		// 存证删除，可以带上删除原因的代码，删除之后留下墓碑
+		// 交易里边没有元数据，先按照元数据最长、背书最多收取权重，执行之后按照实际的长度和背书数量退还
+		#[weight = T::WeightInfo::revoke_claim(Module::<T>::max_metadata_len(), T::MaxEndorsementsPerClaim::get())]
+		pub fn revoke_claim(origin, claim: ClaimDigest, reason: Option<u32>) -> dispatch::DispatchResultWithPostInfo {
 			// 验签+获得调用者
 			let sender = ensure_signed(origin)?;
-			// 检测存证是否存在，如果不存在报错
-			ensure!(Proofs::<T>::contains_key(&claim), Error::<T>::ProofNotExists);
-			// 读取存证信息，主要是需要获得存证的拥有者，block_number 前边加 _ 表示这个变量后边不用，否则编译器会报警告
-			let (owner, _block_number) = Proofs::<T>::get(&claim);
+			// 读取存证信息，如果不存在报错，主要是需要获得存证的拥有者
+			let proof = Proofs::<T>::get(&claim).ok_or(Error::<T>::ProofNotExists)?;
 			// 如果存证的拥有者和调用者不是一个人，报错
-			ensure!(owner == sender, Error::<T>::NotClaimOwner);
-			// 删除存证
-			Proofs::<T>::remove(&claim);
-			// 触发存证删除的事件
-			Self::deposit_event(RawEvent::ClaimRemoved(sender, claim));
-			Ok(())
+			ensure!(proof.owner == sender, Error::<T>::NotClaimOwner);
+			// 多人共有的存证需要通过共有人同意才能删除
+			ensure!(!JointOwners::<T>::contains_key(&claim), Error::<T>::JointApprovalRequired);
+			// 有异议或者被冻结的存证不能删除
+			Self::ensure_not_locked(&claim)?;
+			// 删除存证，并释放创建存证时质押的押金，触发存证删除的事件
+			let endorsements = Self::endorsement_count(&claim);
+			Self::do_revoke(&claim, &proof, Some(sender), reason);
+			Ok(Some(T::WeightInfo::revoke_claim(Self::metadata_len(&proof.metadata), endorsements)).into())
 		}
-		// 存证转让
-		#[weight = 10_000]
-		pub fn transfer_claim(origin, claim: Vec<u8>, receiver: T::AccountId) -> dispatch::DispatchResult {
+		// 存证转让：只是发起转让，需要接收人确认之后才会真正转移
+		#[weight = T::WeightInfo::transfer_claim(Module::<T>::max_metadata_len())]
+		pub fn transfer_claim(origin, claim: ClaimDigest, receiver: T::AccountId) -> dispatch::DispatchResultWithPostInfo {
 			// 验签+获得调用者
 			let sender = ensure_signed(origin)?;
-			// 检测存证是否存在，如果不存在，报错
+			// 获取存证信息，如果不存在，报错，主要是需要获得存证拥有者
+			let proof = Proofs::<T>::get(&claim).ok_or(Error::<T>::ProofNotExists)?;
+			// 检测调用者是不是拥有者或者拥有者授权的账号，如果不是报错
+			Self::ensure_owner_or_approved(&claim, &proof, &sender)?;
+			// 多人共有的存证需要通过共有人同意才能转让
+			ensure!(!JointOwners::<T>::contains_key(&claim), Error::<T>::JointApprovalRequired);
+			Self::ensure_not_locked(&claim)?;
+
+			Self::do_offer_transfer(&claim, &proof, receiver)?;
+			Ok(Some(T::WeightInfo::transfer_claim(Self::metadata_len(&proof.metadata))).into())
+		}
+		// 接收人确认转让，存证和押金在这个时候才转移
+		#[weight = T::WeightInfo::accept_transfer(Module::<T>::max_metadata_len())]
+		pub fn accept_transfer(origin, claim: ClaimDigest) -> dispatch::DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			let offer = PendingTransfers::<T>::get(&claim).ok_or(Error::<T>::NoPendingTransfer)?;
+			ensure!(offer.receiver == sender, Error::<T>::NotTransferReceiver);
+			let proof = Proofs::<T>::get(&claim).ok_or(Error::<T>::ProofNotExists)?;
+			Self::ensure_not_locked(&claim)?;
+
+			let len = Self::metadata_len(&proof.metadata);
+			Self::do_transfer(&claim, proof, sender)?;
+			Ok(Some(T::WeightInfo::accept_transfer(len)).into())
+		}
+		// 接收人拒绝转让
+		#[weight = T::DbWeight::get().reads_writes(2, 2) + 20_000_000]
+		pub fn reject_transfer(origin, claim: ClaimDigest) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let offer = PendingTransfers::<T>::get(&claim).ok_or(Error::<T>::NoPendingTransfer)?;
+			ensure!(offer.receiver == sender, Error::<T>::NotTransferReceiver);
+
+			Self::clear_pending_transfer(&claim);
+			Self::deposit_event(RawEvent::ClaimTransferRejected(sender, claim));
+			Ok(())
+		}
+		// 存证拥有者或者授权的账号取消还没有被确认的转让
+		#[weight = T::DbWeight::get().reads_writes(6, 2) + 25_000_000]
+		pub fn cancel_transfer(origin, claim: ClaimDigest) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let proof = Proofs::<T>::get(&claim).ok_or(Error::<T>::ProofNotExists)?;
+			Self::ensure_owner_or_approved(&claim, &proof, &sender)?;
+			// 多人共有的存证，转让是大家一起同意的，不能由一个人取消
+			ensure!(!JointOwners::<T>::contains_key(&claim), Error::<T>::JointApprovalRequired);
+			ensure!(PendingTransfers::<T>::contains_key(&claim), Error::<T>::NoPendingTransfer);
+
+			Self::clear_pending_transfer(&claim);
+			Self::deposit_event(RawEvent::ClaimTransferCancelled(sender, claim));
+			Ok(())
+		}
+		// 存证延期：过期区块只能往后调，传 None 表示改为永久有效
+		#[weight = T::DbWeight::get().reads_writes(8, 6) + 40_000_000]
+		pub fn extend_claim(origin, claim: ClaimDigest, expires_at: Option<T::BlockNumber>) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let mut proof = Proofs::<T>::get(&claim).ok_or(Error::<T>::ProofNotExists)?;
+			ensure!(proof.owner == sender, Error::<T>::NotClaimOwner);
+			ensure!(!JointOwners::<T>::contains_key(&claim), Error::<T>::JointApprovalRequired);
+			Self::ensure_not_locked(&claim)?;
+			let current = proof.expires_at.ok_or(Error::<T>::ExpiryNotExtended)?;
+			if let Some(new_expiry) = expires_at {
+				ensure!(new_expiry > current, Error::<T>::ExpiryNotExtended);
+				ensure!(Self::claim_expiries_count(new_expiry) < T::MaxExpiriesPerBlock::get(), Error::<T>::TooManyExpiries);
+			}
+
+			proof.expires_at = expires_at;
+			// 过期区块改为永久有效会少占用存储，押金也跟着调整
+			Self::refresh_deposit(&claim, &mut proof)?;
+			if let Some(new_expiry) = expires_at {
+				Self::schedule_expiry(&claim, new_expiry)?;
+			}
+			Self::unschedule_expiry(&claim, current);
+			Proofs::<T>::insert(&claim, &proof);
+
+			Self::deposit_event(RawEvent::ClaimExtended(sender, claim, expires_at));
+			Ok(())
+		}
+		// 把存证设置为多人共有，只有单人拥有的存证的拥有者可以设置，拥有者会自动加入共有人名单
+		#[weight = T::DbWeight::get().reads_writes(5, 2) + 30_000_000]
+		pub fn set_joint_owners(origin, claim: ClaimDigest, co_owners: Vec<T::AccountId>, threshold: u32) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let proof = Proofs::<T>::get(&claim).ok_or(Error::<T>::ProofNotExists)?;
+			ensure!(proof.owner == sender, Error::<T>::NotClaimOwner);
+			ensure!(!JointOwners::<T>::contains_key(&claim), Error::<T>::AlreadyJointClaim);
+			Self::ensure_not_locked(&claim)?;
+			// 单独发起的转让在多人共有之后就没有人能取消了，所以必须先处理掉
+			ensure!(!PendingTransfers::<T>::contains_key(&claim), Error::<T>::TransferAlreadyPending);
+
+			let mut owners = co_owners;
+			owners.push(sender.clone());
+			owners.sort();
+			owners.dedup();
+			ensure!(owners.len() as u32 <= T::MaxCoOwners::get(), Error::<T>::TooManyCoOwners);
+			ensure!(threshold > 0 && threshold <= owners.len() as u32, Error::<T>::InvalidThreshold);
+
+			// 多人共有之后不能单独出售，之前的挂单取消
+			ClaimPrices::<T>::remove(&claim);
+			JointOwners::<T>::insert(&claim, JointOwnership { owners: owners.clone(), threshold });
+			Self::deposit_event(RawEvent::JointOwnersSet(sender, claim, owners, threshold));
+			Ok(())
+		}
+		// 共有人发起一个需要同意的操作，发起人自动同意，同意人数够了马上执行
+		// 同意人数为 1 的时候会马上删除存证，按照删除存证的权重收取
+		#[weight = T::WeightInfo::revoke_claim(Module::<T>::max_metadata_len(), T::MaxEndorsementsPerClaim::get())]
+		pub fn propose_joint_action(origin, claim: ClaimDigest, action: JointAction<T::AccountId>) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let joint = JointOwners::<T>::get(&claim).ok_or(Error::<T>::NotJointClaim)?;
+			ensure!(joint.owners.contains(&sender), Error::<T>::NotCoOwner);
+			ensure!(!JointProposals::<T>::contains_key(&claim), Error::<T>::ProposalAlreadyPending);
+			Self::ensure_not_locked(&claim)?;
+
+			let proposal = JointProposal { action: action.clone(), approvals: vec![sender.clone()] };
+			Self::approve_or_execute(&claim, &joint, proposal, RawEvent::JointActionProposed(sender, claim, action))
+		}
+		// 共有人同意等待中的操作，同意人数够了马上执行
+		#[weight = T::WeightInfo::revoke_claim(Module::<T>::max_metadata_len(), T::MaxEndorsementsPerClaim::get())]
+		pub fn approve_joint_action(origin, claim: ClaimDigest) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let joint = JointOwners::<T>::get(&claim).ok_or(Error::<T>::NotJointClaim)?;
+			ensure!(joint.owners.contains(&sender), Error::<T>::NotCoOwner);
+			let mut proposal = JointProposals::<T>::get(&claim).ok_or(Error::<T>::NoPendingProposal)?;
+			ensure!(!proposal.approvals.contains(&sender), Error::<T>::AlreadyApproved);
+			Self::ensure_not_locked(&claim)?;
+
+			proposal.approvals.push(sender.clone());
+			let approvals = proposal.approvals.len() as u32;
+			Self::approve_or_execute(&claim, &joint, proposal, RawEvent::JointActionApproved(sender, claim, approvals))
+		}
+		// 任何一个共有人都可以取消等待中的操作
+		#[weight = T::DbWeight::get().reads_writes(2, 1) + 20_000_000]
+		pub fn cancel_joint_action(origin, claim: ClaimDigest) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let joint = JointOwners::<T>::get(&claim).ok_or(Error::<T>::NotJointClaim)?;
+			ensure!(joint.owners.contains(&sender), Error::<T>::NotCoOwner);
+			ensure!(JointProposals::<T>::contains_key(&claim), Error::<T>::NoPendingProposal);
+
+			JointProposals::<T>::remove(&claim);
+			Self::deposit_event(RawEvent::JointActionCancelled(sender, claim));
+			Ok(())
+		}
+		// 添加公证人，只有 NotaryOrigin 可以调用
+		#[weight = T::DbWeight::get().reads_writes(1, 1) + 15_000_000]
+		pub fn add_notary(origin, notary: T::AccountId) -> dispatch::DispatchResult {
+			T::NotaryOrigin::ensure_origin(origin)?;
+			ensure!(!Notaries::<T>::contains_key(&notary), Error::<T>::AlreadyNotary);
+
+			Notaries::<T>::insert(&notary, system::Module::<T>::block_number());
+			Self::deposit_event(RawEvent::NotaryAdded(notary));
+			Ok(())
+		}
+		// 移除公证人，之前做过的背书保留，作为历史记录
+		#[weight = T::DbWeight::get().reads_writes(1, 1) + 15_000_000]
+		pub fn remove_notary(origin, notary: T::AccountId) -> dispatch::DispatchResult {
+			T::NotaryOrigin::ensure_origin(origin)?;
+			ensure!(Notaries::<T>::contains_key(&notary), Error::<T>::NotNotary);
+
+			Notaries::<T>::remove(&notary);
+			Self::deposit_event(RawEvent::NotaryRemoved(notary));
+			Ok(())
+		}
+		// 公证人为存证背书
+		#[weight = T::DbWeight::get().reads_writes(4, 2) + 25_000_000]
+		pub fn endorse_claim(origin, claim: ClaimDigest) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(Notaries::<T>::contains_key(&sender), Error::<T>::NotNotary);
 			ensure!(Proofs::<T>::contains_key(&claim), Error::<T>::ProofNotExists);
-			// 获取存证信息，主要是需要获得存证拥有者
-			let (owner, _block_number) = Proofs::<T>::get(&claim);
-			// 检测拥有者和调用者是不是一个人，如果不是报错
-			ensure!( owner == sender , Error::<T>::NotClaimOwner);
-			// 更新存证信息，将拥有人修改为指定的账号，用 insert ，因为是 hashmap 会自动实现为替换
-			Proofs::<T>::insert(&claim,(receiver.clone(), system::Module::<T>::block_number()));
-			// 触发存证转让的事件
-			Self::deposit_event(RawEvent::ClaimTransfered(sender, claim, receiver));
+			ensure!(!Endorsements::<T>::contains_key(&claim, &sender), Error::<T>::AlreadyEndorsed);
+			let count = Self::endorsement_count(&claim);
+			ensure!(count < T::MaxEndorsementsPerClaim::get(), Error::<T>::TooManyEndorsements);
+
+			Endorsements::<T>::insert(&claim, &sender, system::Module::<T>::block_number());
+			EndorsementCount::insert(&claim, count + 1);
+			Self::deposit_event(RawEvent::ClaimEndorsed(sender, claim));
+			Ok(())
+		}
+		// 公证人撤回自己的背书，已经被移除的公证人也可以撤回
+		#[weight = T::DbWeight::get().reads_writes(2, 2) + 20_000_000]
+		pub fn withdraw_endorsement(origin, claim: ClaimDigest) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(Endorsements::<T>::contains_key(&claim, &sender), Error::<T>::NotEndorsed);
+
+			Endorsements::<T>::remove(&claim, &sender);
+			EndorsementCount::mutate_exists(&claim, |count| {
+				*count = count.and_then(|c| c.checked_sub(1)).filter(|c| *c > 0);
+			});
+			Self::deposit_event(RawEvent::EndorsementWithdrawn(sender, claim));
+			Ok(())
+		}
+		// 批量存证：只保存默克尔树的树根和叶子数量，单个文件通过默克尔路径证明
+		// 和单个存证一样需要质押 ClaimDepositBase，删除批量存证的时候释放
+		#[weight = T::WeightInfo::anchor_batch()]
+		pub fn anchor_batch(origin, root: [u8; 32], leaf_count: u32) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(leaf_count > 0, Error::<T>::EmptyBatch);
+			ensure!(!BatchAnchors::<T>::contains_key(&sender, &root), Error::<T>::BatchAlreadyAnchored);
+
+			let deposit = T::ClaimDepositBase::get();
+			T::Currency::reserve(&sender, deposit).map_err(|_| Error::<T>::MoneyNotEnough )?;
+
+			BatchAnchors::<T>::insert(&sender, &root, BatchAnchor {
+				owner: sender.clone(),
+				leaf_count,
+				block_number: system::Module::<T>::block_number(),
+				timestamp: T::Time::now(),
+				deposit,
+			});
+
+			Self::deposit_event(RawEvent::BatchAnchored(sender, root, leaf_count));
+			Ok(())
+		}
+		// 删除批量存证，释放押金
+		#[weight = T::WeightInfo::remove_batch()]
+		pub fn remove_batch(origin, root: [u8; 32]) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let anchor = BatchAnchors::<T>::get(&sender, &root).ok_or(Error::<T>::BatchNotExists)?;
+
+			BatchAnchors::<T>::remove(&sender, &root);
+			T::Currency::unreserve(&sender, anchor.deposit);
+
+			Self::deposit_event(RawEvent::BatchRemoved(sender, root, anchor.deposit));
+			Ok(())
+		}
+		// 修改存证的元数据，元数据变长或者变短，押金会跟着多质押或者释放
+		#[weight = T::WeightInfo::set_claim_metadata(Module::<T>::metadata_len(metadata))]
+		pub fn set_claim_metadata(origin, claim: ClaimDigest, metadata: ClaimMetadata) -> dispatch::DispatchResult {
+			// 验签+获得调用者
+			let sender = ensure_signed(origin)?;
+			let mut proof = Proofs::<T>::get(&claim).ok_or(Error::<T>::ProofNotExists)?;
+			ensure!(proof.owner == sender, Error::<T>::NotClaimOwner);
+			ensure!(!JointOwners::<T>::contains_key(&claim), Error::<T>::JointApprovalRequired);
+			Self::ensure_not_locked(&claim)?;
+			Self::ensure_valid_metadata(&metadata)?;
+
+			let old_deposit = proof.deposit;
+			proof.metadata = metadata;
+			Self::refresh_deposit(&claim, &mut proof)?;
+			Proofs::<T>::insert(&claim, &proof);
+
+			Self::deposit_event(RawEvent::ClaimMetadataUpdated(sender, claim, old_deposit, proof.deposit));
+			Ok(())
+		}
+		// 第三方对存证发起异议，质押押金，等待仲裁裁决
+		#[weight = T::DbWeight::get().reads_writes(3, 2) + 35_000_000]
+		pub fn dispute_claim(origin, claim: ClaimDigest, reason: Option<u32>) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let proof = Proofs::<T>::get(&claim).ok_or(Error::<T>::ProofNotExists)?;
+			ensure!(proof.owner != sender, Error::<T>::CannotDisputeOwnClaim);
+			ensure!(!Disputes::<T>::contains_key(&claim), Error::<T>::DisputeAlreadyPending);
+
+			let deposit = T::DisputeDeposit::get();
+			T::Currency::reserve(&sender, deposit).map_err(|_| Error::<T>::MoneyNotEnough )?;
+			Disputes::<T>::insert(&claim, Dispute {
+				challenger: sender.clone(),
+				block_number: system::Module::<T>::block_number(),
+				reason,
+				deposit,
+			});
+
+			Self::deposit_event(RawEvent::ClaimDisputed(sender, claim, deposit));
+			Ok(())
+		}
+		// 仲裁对异议做出裁决，只有 DisputeOrigin 可以调用
+		// 驳回异议会罚没异议人的押金，其他裁决退还异议人的押金
+		#[weight = T::WeightInfo::revoke_claim(Module::<T>::max_metadata_len(), T::MaxEndorsementsPerClaim::get())]
+		pub fn rule_dispute(origin, claim: ClaimDigest, ruling: DisputeRuling<T::AccountId>) -> dispatch::DispatchResult {
+			T::DisputeOrigin::ensure_origin(origin)?;
+			let dispute = Disputes::<T>::get(&claim).ok_or(Error::<T>::NoPendingDispute)?;
+			let proof = Proofs::<T>::get(&claim).ok_or(Error::<T>::ProofNotExists)?;
+
+			match &ruling {
+				DisputeRuling::Dismiss => {
+					let _ = T::Currency::slash_reserved(&dispute.challenger, dispute.deposit);
+				}
+				DisputeRuling::Freeze => {
+					T::Currency::unreserve(&dispute.challenger, dispute.deposit);
+					// 冻结之后等待中的转让和共有人操作都不能再执行了
+					Self::clear_pending_transfer(&claim);
+					JointProposals::<T>::remove(&claim);
+					FrozenClaims::<T>::insert(&claim, system::Module::<T>::block_number());
+				}
+				DisputeRuling::Reassign(receiver) => {
+					ensure!(*receiver != proof.owner, Error::<T>::TransferToSelf);
+					// 判给新的拥有者，冻结状态也跟着解除，裁决不受新拥有者余额和存证数量的影响
+					Self::do_reassign(&claim, proof, receiver.clone())?;
+					T::Currency::unreserve(&dispute.challenger, dispute.deposit);
+					FrozenClaims::<T>::remove(&claim);
+				}
+				DisputeRuling::Revoke => {
+					T::Currency::unreserve(&dispute.challenger, dispute.deposit);
+					Disputes::<T>::remove(&claim);
+					Self::do_revoke(&claim, &proof, None, dispute.reason);
+				}
+			}
+			Disputes::<T>::remove(&claim);
+
+			Self::deposit_event(RawEvent::DisputeRuled(claim, ruling));
+			Ok(())
+		}
+		// 解除存证的冻结，只有 DisputeOrigin 可以调用
+		#[weight = T::DbWeight::get().reads_writes(1, 1) + 15_000_000]
+		pub fn unfreeze_claim(origin, claim: ClaimDigest) -> dispatch::DispatchResult {
+			T::DisputeOrigin::ensure_origin(origin)?;
+			ensure!(FrozenClaims::<T>::contains_key(&claim), Error::<T>::NotFrozen);
+
+			FrozenClaims::<T>::remove(&claim);
+			Self::deposit_event(RawEvent::ClaimUnfrozen(claim));
+			Ok(())
+		}
+		// 授权一个账号代为转让存证，传 None 表示取消授权，同一时间只能授权一个账号
+		#[weight = T::DbWeight::get().reads_writes(2, 1) + 20_000_000]
+		pub fn approve(origin, claim: ClaimDigest, operator: Option<T::AccountId>) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let proof = Proofs::<T>::get(&claim).ok_or(Error::<T>::ProofNotExists)?;
+			ensure!(proof.owner == sender, Error::<T>::NotClaimOwner);
+			ensure!(!JointOwners::<T>::contains_key(&claim), Error::<T>::JointApprovalRequired);
+			ensure!(operator.as_ref() != Some(&sender), Error::<T>::ApprovalToOwner);
+
+			match &operator {
+				Some(who) => Approvals::<T>::insert(&claim, who),
+				None => Approvals::<T>::remove(&claim),
+			}
+			Self::deposit_event(RawEvent::ClaimApproved(sender, claim, operator));
+			Ok(())
+		}
+		// 授权或者取消授权一个账号代为转让自己所有的存证，包括以后拥有的存证
+		#[weight = T::DbWeight::get().writes(1) + 15_000_000]
+		pub fn set_approval_for_all(origin, operator: T::AccountId, approved: bool) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(operator != sender, Error::<T>::ApprovalToOwner);
+
+			if approved {
+				OperatorApprovals::<T>::insert(&sender, &operator, true);
+			} else {
+				OperatorApprovals::<T>::remove(&sender, &operator);
+			}
+			Self::deposit_event(RawEvent::ApprovalForAll(sender, operator, approved));
+			Ok(())
+		}
+		// 挂单出售存证，传 None 表示取消挂单，多人共有的存证不能挂单
+		#[weight = T::DbWeight::get().reads_writes(4, 1) + 25_000_000]
+		pub fn ask(origin, claim: ClaimDigest, new_price: Option<BalanceOf<T>>) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let proof = Proofs::<T>::get(&claim).ok_or(Error::<T>::ProofNotExists)?;
+			ensure!(proof.owner == sender, Error::<T>::NotClaimOwner);
+			ensure!(!JointOwners::<T>::contains_key(&claim), Error::<T>::JointApprovalRequired);
+			Self::ensure_not_locked(&claim)?;
+
+			ClaimPrices::<T>::mutate_exists(&claim, |price| *price = new_price);
+			Self::deposit_event(RawEvent::ClaimAsk(sender, claim, new_price));
+			Ok(())
+		}
+		// 购买挂单的存证：按照挂单的价格付款给拥有者，存证和押金一起转移给购买者
+		// max_price 是购买者愿意支付的最高价格，防止购买之前拥有者提高价格
+		// 付款和转移存证要么都成功要么都失败
+		// 先按照元数据最长收取权重，执行之后按照实际的长度退还
+		#[weight = Module::<T>::buy_weight(Module::<T>::max_metadata_len())]
+		#[transactional]
+		pub fn buy(origin, claim: ClaimDigest, max_price: BalanceOf<T>) -> dispatch::DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			let proof = Proofs::<T>::get(&claim).ok_or(Error::<T>::ProofNotExists)?;
+			let seller = proof.owner.clone();
+			ensure!(seller != sender, Error::<T>::AlreadyOwned);
+			let price = Self::claim_prices(&claim).ok_or(Error::<T>::NotForSale)?;
+			ensure!(price <= max_price, Error::<T>::PriceTooLow);
+			Self::ensure_not_locked(&claim)?;
+
+			// do_transfer 会清除挂单，质押购买者的押金并释放出售者的押金
+			let len = Self::metadata_len(&proof.metadata);
+			Self::do_transfer(&claim, proof, sender.clone())?;
+			T::Currency::transfer(&sender, &seller, price, ExistenceRequirement::KeepAlive)
+				.map_err(|_| Error::<T>::MoneyNotEnough )?;
+
+			Self::deposit_event(RawEvent::ClaimSold(seller, sender, claim, price));
+			Ok(Some(Self::buy_weight(len)).into())
+		}
+		// 注册一个命名空间，质押押金，编号按顺序分配
+		#[weight = T::DbWeight::get().reads_writes(2, 3) + 35_000_000]
+		pub fn register_namespace(origin) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let id = Self::next_namespace_id();
+			let next_id = id.checked_add(1).ok_or(Error::<T>::NoAvailableNamespaceId)?;
+
+			let deposit = T::NamespaceDeposit::get();
+			T::Currency::reserve(&sender, deposit).map_err(|_| Error::<T>::MoneyNotEnough )?;
+			Namespaces::<T>::insert(id, Namespace { owner: sender.clone(), deposit });
+			NextNamespaceId::put(next_id);
+
+			Self::deposit_event(RawEvent::NamespaceRegistered(sender, id, deposit));
+			Ok(())
+		}
+		// 命名空间的拥有者授权成员在命名空间里边写入存证
+		#[weight = T::DbWeight::get().reads_writes(2, 1) + 20_000_000]
+		pub fn add_namespace_writer(origin, namespace: NamespaceId, writer: T::AccountId) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_namespace_owner(namespace, &sender)?;
+			ensure!(!NamespaceWriters::<T>::contains_key(namespace, &writer), Error::<T>::AlreadyNamespaceWriter);
+
+			NamespaceWriters::<T>::insert(namespace, &writer, system::Module::<T>::block_number());
+			Self::deposit_event(RawEvent::NamespaceWriterAdded(namespace, writer));
+			Ok(())
+		}
+		// 命名空间的拥有者取消成员的授权，成员之前写入的存证保留
+		#[weight = T::DbWeight::get().reads_writes(2, 1) + 20_000_000]
+		pub fn remove_namespace_writer(origin, namespace: NamespaceId, writer: T::AccountId) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_namespace_owner(namespace, &sender)?;
+			ensure!(NamespaceWriters::<T>::contains_key(namespace, &writer), Error::<T>::NotNamespaceWriter);
+
+			NamespaceWriters::<T>::remove(namespace, &writer);
+			Self::deposit_event(RawEvent::NamespaceWriterRemoved(namespace, writer));
 			Ok(())
 		}
 	}
 }
 impl<T: Config> Module<T> {
-	pub fn do_create_claim(sender: T::AccountId, claim: Vec<u8>) -> Result<(), dispatch::DispatchError> {
+	// 创建存证，sender 是存证的拥有者，depositor 质押押金，自己提交的存证两个是同一个账号
+	pub fn do_create_claim(sender: T::AccountId, depositor: T::AccountId, claim: ClaimDigest, metadata: ClaimMetadata, expires_at: Option<T::BlockNumber>) -> Result<(), dispatch::DispatchError> {
 		// 检测存证是否已经存在
 		ensure!(!Proofs::<T>::contains_key(&claim), Error::<T>::ProofAlreadyExists);
-		// 检测存证的长度是否超过限制
-		ensure!( T::MaxClaimLength::get() >= claim.len() as u32, Error::<T>::ProofTooLong);
+		// 被删除过的存证不能再次创建
+		ensure!(!Tombstones::<T>::contains_key(&claim), Error::<T>::ProofRevoked);
+		// 命名空间里边的存证只有命名空间的拥有者和授权的成员可以创建
+		if let Some(namespace) = claim.namespace {
+			Self::ensure_namespace_writer(namespace, &sender)?;
+		}
+		// 检测元数据的长度是否超过限制
+		Self::ensure_valid_metadata(&metadata)?;
+		// 检测拥有的存证数量是否超过限制
+		Self::ensure_can_own_more(&sender)?;
+		let now = system::Module::<T>::block_number();
+		// 过期区块必须在当前区块之后
+		if let Some(expiry) = expires_at {
+			ensure!(expiry > now, Error::<T>::ExpiryInPast);
+			ensure!(Self::claim_expiries_count(expiry) < T::MaxExpiriesPerBlock::get(), Error::<T>::TooManyExpiries);
+		}
+		let mut proof = Proof {
+			owner: sender.clone(),
+			depositor,
+			block_number: now,
+			timestamp: T::Time::now(),
+			deposit: Default::default(),
+			metadata,
+			expires_at,
+		};
+		// 按照存证占用的存储大小质押押金，如果余额不够会报错
+		proof.deposit = Self::claim_deposit(&claim, &proof);
+		T::Currency::reserve(&proof.depositor, proof.deposit).map_err(|_| Error::<T>::MoneyNotEnough )?;
 		// 存入存证数据
-		Proofs::<T>::insert(&claim,(sender.clone(), system::Module::<T>::block_number()));
+		Proofs::<T>::insert(&claim, &proof);
+		// 保存拥有者拥有的存证数据
+		Self::insert_owned_claim(&sender, &claim);
+		// 第一条流转记录就是创建者
+		Self::record_provenance(&claim, &proof);
+		// 登记过期的区块，前边已经检测过数量，这里不会失败
+		if let Some(expiry) = expires_at {
+			Self::schedule_expiry(&claim, expiry)?;
+		}
 		// 触发存证写入成功的时间
-		Self::deposit_event(RawEvent::ClaimCreated(sender, claim));
-		
+		Self::deposit_event(RawEvent::ClaimCreated(sender, claim, proof.deposit));
+
+		Ok(())
+	}
+
+	// 删除存证，留下墓碑并触发存证删除的事件
+	fn do_revoke(claim: &ClaimDigest, proof: &ProofOf<T>, revoker: Option<T::AccountId>, reason: Option<u32>) {
+		Self::remove_claim(claim, proof);
+		Tombstones::<T>::insert(claim, Tombstone {
+			revoker,
+			block_number: system::Module::<T>::block_number(),
+			reason,
+		});
+		Self::deposit_event(RawEvent::ClaimRemoved(proof.owner.clone(), *claim, proof.deposit));
+	}
+
+	fn ensure_can_offer(claim: &ClaimDigest, proof: &ProofOf<T>, receiver: &T::AccountId) -> dispatch::DispatchResult {
+		// 不能转让给自己
+		ensure!( *receiver != proof.owner, Error::<T>::TransferToSelf);
+		// 同一时间只能有一个等待确认的转让，需要先取消之前的
+		ensure!( !PendingTransfers::<T>::contains_key(claim), Error::<T>::TransferAlreadyPending);
+		Ok(())
+	}
+
+	// 发起存证转让，等待接收人确认
+	fn do_offer_transfer(claim: &ClaimDigest, proof: &ProofOf<T>, receiver: T::AccountId) -> dispatch::DispatchResult {
+		Self::ensure_can_offer(claim, proof, &receiver)?;
+
+		let expires_at = system::Module::<T>::block_number().saturating_add(T::TransferOfferTimeout::get());
+		PendingTransfers::<T>::insert(claim, PendingTransfer { receiver: receiver.clone(), expires_at });
+		TransferExpiries::<T>::insert(expires_at, claim, claim);
+
+		Self::deposit_event(RawEvent::ClaimTransferOffered(proof.owner.clone(), *claim, receiver, expires_at));
+		Ok(())
+	}
+
+	// 同意人数够了就执行操作并清理掉，否则保存等待更多共有人同意
+	// 执行之前先做完所有检测，检测不通过的时候不会修改任何数据，也不会触发事件
+	fn approve_or_execute(
+		claim: &ClaimDigest,
+		joint: &JointOwnership<T::AccountId>,
+		proposal: JointProposal<T::AccountId>,
+		event: Event<T>,
+	) -> dispatch::DispatchResult {
+		if (proposal.approvals.len() as u32) < joint.threshold {
+			JointProposals::<T>::insert(claim, proposal);
+			Self::deposit_event(event);
+			return Ok(());
+		}
+
+		let proof = Proofs::<T>::get(claim).ok_or(Error::<T>::ProofNotExists)?;
+		if let JointAction::Transfer(receiver) = &proposal.action {
+			Self::ensure_can_offer(claim, &proof, receiver)?;
+		}
+		Self::deposit_event(event);
+		match proposal.action {
+			JointAction::Revoke => Self::do_revoke(claim, &proof, Some(proof.owner.clone()), None),
+			JointAction::Transfer(receiver) => Self::do_offer_transfer(claim, &proof, receiver)?,
+		}
+		JointProposals::<T>::remove(claim);
+		Ok(())
+	}
+
+	// 把存证转移给新的拥有者：押金、拥有者索引、流转记录都跟着更新，等待确认的转让也会清理掉
+	fn do_transfer(claim: &ClaimDigest, proof: ProofOf<T>, receiver: T::AccountId) -> dispatch::DispatchResult {
+		let sender = proof.owner.clone();
+		// 检测接收人拥有的存证数量是否超过限制
+		Self::ensure_can_own_more(&receiver)?;
+		// 检测流转记录是否已经满了
+		ensure!((Self::claim_history(claim).len() as u32) < T::MaxHistoryLength::get(), Error::<T>::HistoryFull);
+		let mut new_proof = proof.clone();
+		new_proof.owner = receiver.clone();
+		new_proof.depositor = receiver.clone();
+		new_proof.block_number = system::Module::<T>::block_number();
+		new_proof.timestamp = T::Time::now();
+		// 押金跟着存证走：按照当前的配置质押接收人的押金，再释放之前质押的押金
+		new_proof.deposit = Self::claim_deposit(claim, &new_proof);
+		T::Currency::reserve(&receiver, new_proof.deposit).map_err(|_| Error::<T>::MoneyNotEnough )?;
+		T::Currency::unreserve(&proof.depositor, proof.deposit);
+		// 更新存证信息，将拥有人修改为指定的账号，用 insert ，因为是 hashmap 会自动实现为替换
+		Proofs::<T>::insert(claim, &new_proof);
+		// 从之前的拥有人中删除关系
+		Self::remove_owned_claim(&sender, claim);
+		Self::insert_owned_claim(&receiver, claim);
+		// 追加一条流转记录
+		Self::record_provenance(claim, &new_proof);
+		// 拥有者变了，之前发起的转让和授权也就失效了，转让之后存证由接收人一个人拥有
+		Self::clear_owner_state(claim);
+		// 触发存证转让的事件
+		Self::deposit_event(RawEvent::ClaimTransfered(sender, *claim, receiver, proof.deposit, new_proof.deposit));
+		Ok(())
+	}
+
+	// 仲裁把存证判给新的拥有者：押金还是由之前质押的账号承担，等新拥有者修改押金的时候再转到他身上
+	// 裁决不检查新拥有者的余额和存证数量；流转记录不能改写，满了的时候和转让一样报错
+	fn do_reassign(claim: &ClaimDigest, proof: ProofOf<T>, receiver: T::AccountId) -> dispatch::DispatchResult {
+		ensure!((Self::claim_history(claim).len() as u32) < T::MaxHistoryLength::get(), Error::<T>::HistoryFull);
+		let sender = proof.owner.clone();
+		let mut new_proof = proof;
+		new_proof.owner = receiver.clone();
+		new_proof.block_number = system::Module::<T>::block_number();
+		new_proof.timestamp = T::Time::now();
+		Proofs::<T>::insert(claim, &new_proof);
+		Self::remove_owned_claim(&sender, claim);
+		Self::insert_owned_claim(&receiver, claim);
+		Self::record_provenance(claim, &new_proof);
+		Self::clear_owner_state(claim);
+		Self::deposit_event(RawEvent::ClaimTransfered(sender, *claim, receiver, new_proof.deposit, new_proof.deposit));
 		Ok(())
 	}
-}
\ No newline at end of file
+
+	// 拥有者变了或者存证删除之后失效的数据：等待接收的转让、授权、挂单、共有人和等待同意的操作
+	fn clear_owner_state(claim: &ClaimDigest) {
+		Self::clear_pending_transfer(claim);
+		Approvals::<T>::remove(claim);
+		ClaimPrices::<T>::remove(claim);
+		JointOwners::<T>::remove(claim);
+		JointProposals::<T>::remove(claim);
+	}
+
+	// 删除存证以及和存证相关的所有数据，并释放押金
+	// 流转记录不在这里删除：删除存证会留下墓碑，流转记录跟着墓碑保留下来，方便以后审计
+	fn remove_claim(claim: &ClaimDigest, proof: &ProofOf<T>) {
+		Proofs::<T>::remove(claim);
+		Self::remove_owned_claim(&proof.owner, claim);
+		// 背书的数量不会超过 MaxEndorsementsPerClaim
+		Endorsements::<T>::remove_prefix(claim);
+		EndorsementCount::remove(claim);
+		Self::clear_owner_state(claim);
+		if let Some(expiry) = proof.expires_at {
+			Self::unschedule_expiry(claim, expiry);
+		}
+		// 存证没有了，异议也就没有意义了，退还异议人的押金
+		if let Some(dispute) = Disputes::<T>::take(claim) {
+			T::Currency::unreserve(&dispute.challenger, dispute.deposit);
+		}
+		FrozenClaims::<T>::remove(claim);
+		T::Currency::unreserve(&proof.depositor, proof.deposit);
+	}
+
+	// 清理在区块 n 过期的存证转让
+	fn expire_transfers(n: T::BlockNumber) -> Weight {
+		let mut expired: u64 = 0;
+		for claim in TransferExpiries::<T>::iter_prefix_values(n) {
+			if let Some(offer) = PendingTransfers::<T>::take(&claim) {
+				Self::deposit_event(RawEvent::ClaimTransferExpired(claim, offer.receiver));
+			}
+			expired += 1;
+		}
+		TransferExpiries::<T>::remove_prefix(n);
+		T::DbWeight::get().reads_writes(1 + expired, 1 + expired)
+	}
+
+	// 清理在区块 n 过期的存证，数量不会超过 MaxExpiriesPerBlock
+	fn expire_claims(n: T::BlockNumber) -> Weight {
+		let count = ClaimExpiriesCount::<T>::take(n);
+		if count == 0 {
+			return T::DbWeight::get().reads_writes(1, 1);
+		}
+		let expiring: Vec<ClaimDigest> = ClaimExpiries::<T>::iter_prefix_values(n).collect();
+		let mut endorsements: u64 = 0;
+		for claim in expiring.iter() {
+			if let Some(proof) = Proofs::<T>::get(claim) {
+				endorsements += Self::endorsement_count(claim) as u64;
+				// remove_claim 里边会清理过期登记，包括 ClaimExpiries 里边的这一条
+				Self::remove_claim(claim, &proof);
+				// 过期的存证没有墓碑，可以重新提交，所以流转记录也一起删除
+				ClaimHistory::<T>::remove(claim);
+				Self::deposit_event(RawEvent::ClaimExpired(proof.owner, *claim, proof.deposit));
+			}
+		}
+		ClaimExpiries::<T>::remove_prefix(n);
+		ClaimExpiriesCount::<T>::remove(n);
+		// 每个过期的存证读 10 次：过期登记、存证、背书数量、拥有者数量、转让、过期登记和数量、异议、异议人和质押人的账户
+		// 写 17 次：存证、拥有者索引和数量、背书数量、授权、挂单、共有人、共有人的操作、转让和转让过期登记、
+		// 过期登记和数量、异议、异议人的账户、冻结、质押人的账户、流转记录，再加上每一条背书的删除
+		let expired = expiring.len() as u64;
+		T::DbWeight::get().reads_writes(1 + 10 * expired, 3 + 17 * expired + endorsements)
+	}
+
+	// 登记存证在某个区块过期
+	fn schedule_expiry(claim: &ClaimDigest, expiry: T::BlockNumber) -> dispatch::DispatchResult {
+		let count = Self::claim_expiries_count(expiry);
+		ensure!(count < T::MaxExpiriesPerBlock::get(), Error::<T>::TooManyExpiries);
+		ClaimExpiries::<T>::insert(expiry, claim, claim);
+		ClaimExpiriesCount::<T>::insert(expiry, count + 1);
+		Ok(())
+	}
+
+	fn unschedule_expiry(claim: &ClaimDigest, expiry: T::BlockNumber) {
+		if ClaimExpiries::<T>::take(expiry, claim).is_some() {
+			ClaimExpiriesCount::<T>::mutate_exists(expiry, |count| {
+				*count = count.map(|c| c.saturating_sub(1)).filter(|c| *c > 0);
+			});
+		}
+	}
+
+	// 调用者是存证的拥有者，或者是拥有者授权的账号
+	fn ensure_owner_or_approved(claim: &ClaimDigest, proof: &ProofOf<T>, who: &T::AccountId) -> dispatch::DispatchResult {
+		ensure!(
+			proof.owner == *who
+				|| Approvals::<T>::get(claim).as_ref() == Some(who)
+				|| OperatorApprovals::<T>::get(&proof.owner, who),
+			Error::<T>::NotClaimOwner
+		);
+		Ok(())
+	}
+
+	fn ensure_namespace_owner(namespace: NamespaceId, who: &T::AccountId) -> dispatch::DispatchResult {
+		let info = Namespaces::<T>::get(namespace).ok_or(Error::<T>::NamespaceNotExists)?;
+		ensure!(info.owner == *who, Error::<T>::NotNamespaceOwner);
+		Ok(())
+	}
+
+	fn ensure_namespace_writer(namespace: NamespaceId, who: &T::AccountId) -> dispatch::DispatchResult {
+		let info = Namespaces::<T>::get(namespace).ok_or(Error::<T>::NamespaceNotExists)?;
+		ensure!(info.owner == *who || NamespaceWriters::<T>::contains_key(namespace, who), Error::<T>::NotNamespaceWriter);
+		Ok(())
+	}
+
+	// 有异议或者被冻结的存证，拥有者不能删除、转让或者修改
+	fn ensure_not_locked(claim: &ClaimDigest) -> dispatch::DispatchResult {
+		ensure!(!Disputes::<T>::contains_key(claim), Error::<T>::ClaimDisputed);
+		ensure!(!FrozenClaims::<T>::contains_key(claim), Error::<T>::ClaimFrozen);
+		Ok(())
+	}
+
+	fn clear_pending_transfer(claim: &ClaimDigest) {
+		if let Some(offer) = PendingTransfers::<T>::take(claim) {
+			TransferExpiries::<T>::remove(offer.expires_at, claim);
+		}
+	}
+
+	fn ensure_can_own_more(owner: &T::AccountId) -> dispatch::DispatchResult {
+		ensure!(Self::owned_claims_count(owner) < T::MaxClaimsPerAccount::get(), Error::<T>::TooManyClaims);
+		Ok(())
+	}
+
+	// 维护拥有者到存证的反向索引，存证数量一起更新
+	fn insert_owned_claim(owner: &T::AccountId, claim: &ClaimDigest) {
+		OwnedClaims::<T>::insert(owner, claim, claim);
+		OwnedClaimsCount::<T>::mutate(owner, |count| *count = count.saturating_add(1));
+	}
+
+	fn remove_owned_claim(owner: &T::AccountId, claim: &ClaimDigest) {
+		OwnedClaims::<T>::remove(owner, claim);
+		OwnedClaimsCount::<T>::mutate_exists(owner, |count| {
+			*count = count.map(|c| c.saturating_sub(1)).filter(|c| *c > 0);
+		});
+	}
+
+	fn record_provenance(claim: &ClaimDigest, proof: &ProofOf<T>) {
+		ClaimHistory::<T>::append(claim, ProvenanceRecord {
+			owner: proof.owner.clone(),
+			block_number: proof.block_number,
+			timestamp: proof.timestamp,
+		});
+	}
+
+	// 检测元数据各个字段的长度是否超过限制
+	fn ensure_valid_metadata(metadata: &ClaimMetadata) -> dispatch::DispatchResult {
+		ensure!(T::MaxTitleLength::get() >= metadata.title.len() as u32, Error::<T>::TitleTooLong);
+		ensure!(T::MaxContentTypeLength::get() >= metadata.content_type.len() as u32, Error::<T>::ContentTypeTooLong);
+		if let Some(uri) = &metadata.uri {
+			ensure!(T::MaxUriLength::get() >= uri.len() as u32, Error::<T>::UriTooLong);
+		}
+		Ok(())
+	}
+
+	// 元数据的长度：标题、文件类型、链下地址的字节数之和，用来计算交易的权重
+	pub fn metadata_len(metadata: &ClaimMetadata) -> u32 {
+		let uri_len = metadata.uri.as_ref().map_or(0, |uri| uri.len());
+		(metadata.title.len() + metadata.content_type.len() + uri_len) as u32
+	}
+
+	// 购买存证：和接收转让一样转移存证，再加上读挂单的价格和一次转账（两个账户的读写）
+	pub fn buy_weight(len: u32) -> Weight {
+		T::WeightInfo::accept_transfer(len)
+			.saturating_add(T::DbWeight::get().reads_writes(3, 2))
+			.saturating_add(50_000_000)
+	}
+
+	pub fn max_metadata_len() -> u32 {
+		T::MaxTitleLength::get()
+			.saturating_add(T::MaxContentTypeLength::get())
+			.saturating_add(T::MaxUriLength::get())
+	}
+
+	// 存证内容变化之后重新计算押金，多退少补，押金不够的时候报错并且不会修改任何数据
+	// 押金不是拥有者质押的（代为提交或者仲裁判给的存证），拥有者第一次修改的时候把押金转到拥有者身上，
+	// 不能在没有同意的情况下多质押别人的钱
+	fn refresh_deposit(claim: &ClaimDigest, proof: &mut ProofOf<T>) -> dispatch::DispatchResult {
+		let old_deposit = proof.deposit;
+		if proof.depositor != proof.owner {
+			let old_depositor = sp_std::mem::replace(&mut proof.depositor, proof.owner.clone());
+			proof.deposit = Self::claim_deposit(claim, proof);
+			T::Currency::reserve(&proof.owner, proof.deposit).map_err(|_| Error::<T>::MoneyNotEnough )?;
+			T::Currency::unreserve(&old_depositor, old_deposit);
+			return Ok(());
+		}
+		proof.deposit = Self::claim_deposit(claim, proof);
+		match proof.deposit.cmp(&old_deposit) {
+			Ordering::Greater => {
+				T::Currency::reserve(&proof.depositor, proof.deposit - old_deposit).map_err(|_| Error::<T>::MoneyNotEnough )?;
+			}
+			Ordering::Less => {
+				T::Currency::unreserve(&proof.depositor, old_deposit - proof.deposit);
+			}
+			Ordering::Equal => (),
+		}
+		Ok(())
+	}
+
+	// 计算存证需要质押的押金：基础押金 + 存证（KEY 和值）编码后的字节数 * 每字节押金
+	fn claim_deposit(claim: &ClaimDigest, proof: &ProofOf<T>) -> BalanceOf<T> {
+		let bytes = (claim.encoded_size() + proof.encoded_size()) as u32;
+		T::ClaimDepositBase::get().saturating_add(T::ClaimDepositPerByte::get().saturating_mul(bytes.into()))
+	}
+
+	// 代为提交存证时拥有者需要签名的内容：固定的前缀 + 创世区块哈希、存证、元数据、过期区块和 nonce 编码后的字节
+	pub fn relayed_claim_payload(
+		claim: &ClaimDigest,
+		metadata: &ClaimMetadata,
+		expires_at: &Option<T::BlockNumber>,
+		nonce: u64,
+	) -> Vec<u8> {
+		let mut payload = b"poe/create_claim".to_vec();
+		// 加上创世区块的哈希，签名不能拿到其他链上重放
+		let genesis_hash = system::Module::<T>::block_hash(T::BlockNumber::zero());
+		(genesis_hash, claim, metadata, expires_at, nonce).encode_to(&mut payload);
+		payload
+	}
+
+	// 给 runtime api 使用：根据摘要查询存证的拥有者、区块和时间戳，不存在返回 None
+	pub fn verify_claim(claim: &ClaimDigest) -> Option<ProofOf<T>> {
+		Proofs::<T>::get(claim)
+	}
+
+	// 给 runtime api 使用：分页查询某个账号拥有的存证，page 从 0 开始，每页最多 MaxClaimsPerAccount 个
+	pub fn claims_of(owner: &T::AccountId, page: u32, page_size: u32) -> Vec<ClaimDigest> {
+		let page_size = page_size.min(T::MaxClaimsPerAccount::get());
+		OwnedClaims::<T>::iter_prefix_values(owner)
+			.skip(page.saturating_mul(page_size) as usize)
+			.take(page_size as usize)
+			.collect()
+	}
+
+	// 给 runtime api 使用：查询为存证背书的公证人以及背书的区块
+	pub fn endorsements_of(claim: &ClaimDigest) -> Vec<(T::AccountId, T::BlockNumber)> {
+		Endorsements::<T>::iter_prefix(claim).collect()
+	}
+
+	// 给 runtime api 使用：验证某个文件哈希（叶子）属于 owner 提交的批量存证，验证通过返回批量存证的信息
+	// 默克尔树的规则：父节点 = blake2_256(左节点 ++ 右节点)，某一层节点数量为奇数时，最后一个节点和自己配对，
+	// 所以路径长度一定是树的高度，index 是叶子从 0 开始的位置
+	pub fn verify_batch_membership(owner: &T::AccountId, root: &[u8; 32], leaf: [u8; 32], index: u32, path: &[[u8; 32]]) -> Option<BatchAnchorOf<T>> {
+		let anchor = BatchAnchors::<T>::get(owner, root)?;
+		if index >= anchor.leaf_count || path.len() != Self::merkle_depth(anchor.leaf_count) {
+			return None;
+		}
+
+		let mut node = leaf;
+		let mut position = index;
+		for sibling in path {
+			let mut pair = [0u8; 64];
+			if position % 2 == 0 {
+				pair[..32].copy_from_slice(&node);
+				pair[32..].copy_from_slice(sibling);
+			} else {
+				pair[..32].copy_from_slice(sibling);
+				pair[32..].copy_from_slice(&node);
+			}
+			node = blake2_256(&pair);
+			position /= 2;
+		}
+
+		if node == *root { Some(anchor) } else { None }
+	}
+
+	// 默克尔树的高度，只有一个叶子的时候树根就是叶子本身
+	fn merkle_depth(leaf_count: u32) -> usize {
+		let mut depth = 0;
+		let mut width = leaf_count;
+		while width > 1 {
+			width = (width + 1) / 2;
+			depth += 1;
+		}
+		depth
+	}
+}
@@ -0,0 +1,45 @@
+//! pallet_poe 的存储迁移
+
+use super::*;
+use frame_support::{storage::migration::storage_key_iter, Blake2_128Concat};
+use sp_runtime::traits::Zero;
+
+// V1_0_0 迁移到 V2_0_0：旧的存证 KEY 是存证的原始内容，值是 (拥有者, 区块)
+// 迁移之后 KEY 是原始内容的 blake2_256 摘要，拥有者和区块保留，同时补上拥有者索引和第一条流转记录
+// 旧的存证没有质押押金，押金记为 0，时间戳、元数据也没有，使用默认值，永久有效
+pub fn migrate_to_v2<T: Config>() -> Weight {
+	if StorageVersion::get() != Releases::V1_0_0 {
+		return T::DbWeight::get().reads(1);
+	}
+
+	// 新旧 KEY 在同一个前缀下边，先把旧的数据全部取出来并删除，再写入新的数据
+	let old_proofs: Vec<(Vec<u8>, (T::AccountId, T::BlockNumber))> =
+		storage_key_iter::<Vec<u8>, (T::AccountId, T::BlockNumber), Blake2_128Concat>(b"PoeModule", b"Proofs")
+			.drain()
+			.collect();
+
+	let migrated = old_proofs.len() as Weight;
+	for (content, (owner, block_number)) in old_proofs {
+		let claim = ClaimDigest {
+			algorithm: DigestAlgorithm::Blake2_256,
+			hash: blake2_256(&content),
+			namespace: None,
+		};
+		let proof = Proof {
+			owner: owner.clone(),
+			depositor: owner.clone(),
+			block_number,
+			timestamp: Default::default(),
+			deposit: Zero::zero(),
+			metadata: Default::default(),
+			expires_at: None,
+		};
+		Proofs::<T>::insert(&claim, &proof);
+		Module::<T>::insert_owned_claim(&owner, &claim);
+		Module::<T>::record_provenance(&claim, &proof);
+	}
+
+	StorageVersion::put(Releases::V2_0_0);
+	// 每个存证：读写旧的存证，写新的存证、拥有者索引和数量、流转记录
+	T::DbWeight::get().reads_writes(1 + 2 * migrated, 1 + 5 * migrated)
+}
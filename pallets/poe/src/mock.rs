@@ -2,7 +2,7 @@ use crate as pallet_poe;
 use sp_core::H256;
 use frame_support::parameter_types;
 use sp_runtime::{
-	traits::{BlakeTwo256, IdentityLookup}, testing::Header,
+	traits::{BlakeTwo256, IdentityLookup}, testing::{Header, TestSignature, UintAuthorityId},
 };
 use frame_system as system;
 
@@ -17,7 +17,9 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Module, Call, Config, Storage, Event<T>},
-		PoeModule: pallet_poe::{Module, Call, Storage, Event<T>},
+		Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+		Timestamp: pallet_timestamp::{Module, Call, Storage, Inherent},
+		PoeModule: pallet_poe::{Module, Call, Config, Storage, Event<T>},
 	}
 );
 
@@ -44,7 +46,7 @@ impl system::Config for Test {
 	type BlockHashCount = BlockHashCount;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
@@ -52,14 +54,91 @@ impl system::Config for Test {
 }
 
 parameter_types! {
-	pub const MaxClaimLength: u32 = 6;
+	pub const ExistentialDeposit: u64 = 1;
 }
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const MinimumPeriod: u64 = 5;
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = MinimumPeriod;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const ClaimDepositBase: u64 = 10;
+	pub const ClaimDepositPerByte: u64 = 1;
+	pub const MaxTitleLength: u32 = 16;
+	pub const MaxContentTypeLength: u32 = 16;
+	pub const MaxUriLength: u32 = 32;
+	pub const MaxClaimsPerAccount: u32 = 3;
+	pub const MaxHistoryLength: u32 = 5;
+	pub const TransferOfferTimeout: u64 = 10;
+	pub const MaxExpiriesPerBlock: u32 = 2;
+	pub const MaxCoOwners: u32 = 3;
+	pub const MaxEndorsementsPerClaim: u32 = 2;
+	pub const DisputeDeposit: u64 = 50;
+	pub const NamespaceDeposit: u64 = 100;
+}
+
 impl pallet_poe::Config for Test {
 	type Event = Event;
-	type MaxClaimLength = MaxClaimLength;
+	type Time = Timestamp;
+	type Currency = Balances;
+	type ClaimDepositBase = ClaimDepositBase;
+	type ClaimDepositPerByte = ClaimDepositPerByte;
+	type MaxTitleLength = MaxTitleLength;
+	type MaxContentTypeLength = MaxContentTypeLength;
+	type MaxUriLength = MaxUriLength;
+	type MaxClaimsPerAccount = MaxClaimsPerAccount;
+	type MaxHistoryLength = MaxHistoryLength;
+	type TransferOfferTimeout = TransferOfferTimeout;
+	type MaxExpiriesPerBlock = MaxExpiriesPerBlock;
+	type NotaryOrigin = frame_system::EnsureRoot<u64>;
+	type MaxCoOwners = MaxCoOwners;
+	type MaxEndorsementsPerClaim = MaxEndorsementsPerClaim;
+	type DisputeOrigin = frame_system::EnsureRoot<u64>;
+	type DisputeDeposit = DisputeDeposit;
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
+	type NamespaceDeposit = NamespaceDeposit;
+	type WeightInfo = ();
+}
+
+// 基准测试里边用 TestSignature 签名，签名的账号固定为 9
+#[cfg(feature = "runtime-benchmarks")]
+impl crate::benchmarking::BenchmarkSigner<TestSignature> for UintAuthorityId {
+	fn sign(payload: &[u8]) -> (Self, TestSignature) {
+		(UintAuthorityId(9), TestSignature(9, payload.to_vec()))
+	}
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	// 因为创建存证需要质押押金，所以需要给一些账户初始化一些钱，账户 9 没有钱
+	let mut t = system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 1_000), (2, 1_000), (3, 1_000)],
+	}.assimilate_storage(&mut t)
+	.unwrap();
+	pallet_poe::GenesisConfig::default().assimilate_storage::<Test>(&mut t).unwrap();
+	let mut ext: sp_io::TestExternalities = t.into();
+	// 区块 0 不会记录事件，所以从区块 1 开始
+	ext.execute_with(|| System::set_block_number(1));
+	ext
 }
\ No newline at end of file
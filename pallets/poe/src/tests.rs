@@ -1,15 +1,117 @@
-
 use crate::{Error, mock::*};
+use crate::mock::Event;
 use super::*;
-use frame_support::{assert_noop, assert_ok};
+use frame_support::{assert_noop, assert_ok, traits::{ReservableCurrency, OnFinalize, OnInitialize, OnRuntimeUpgrade}};
+use sp_runtime::testing::{TestSignature, UintAuthorityId};
+
+// 摘要 34 字节 + 存证信息 41 字节 + 空的元数据 11 字节，按照 mock 的配置押金为 10 + 86 * 1
+const CLAIM_DEPOSIT: u64 = 96;
+
+// 构造一个测试用的存证摘要
+fn digest(n: u8) -> ClaimDigest {
+	ClaimDigest { algorithm: DigestAlgorithm::Blake2_256, hash: [n; 32], namespace: None }
+}
+
+fn run_to_block( n: u64) {
+	while System::block_number() < n {
+		PoeModule::on_finalize(System::block_number());
+		System::on_finalize(System::block_number());
+		System::set_block_number(System::block_number()+1);
+		System::on_initialize(System::block_number());
+		PoeModule::on_initialize(System::block_number());
+	}
+}
+
+// 发起转让并由接收人确认
+fn transfer(from: u64, claim: ClaimDigest, to: u64) {
+	assert_ok!(PoeModule::transfer_claim(Origin::signed(from), claim, to));
+	assert_ok!(PoeModule::accept_transfer(Origin::signed(to), claim));
+}
 
 // 测试存证成功添加的情况
 #[test]
 fn create_claim_works(){
 	new_test_ext().execute_with(|| {
-		let claim = vec![0,1];
-		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
-		assert_eq!(Proofs::<Test>::get(&claim),(1, system::Module::<Test>::block_number()));
+		let claim = digest(1);
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None));
+		assert_eq!(
+			Proofs::<Test>::get(&claim),
+			Some(Proof { owner: 1, depositor: 1, block_number: system::Module::<Test>::block_number(), timestamp: 0, deposit: CLAIM_DEPOSIT, metadata: ClaimMetadata::default(), expires_at: None })
+		);
+		assert_eq!(Balances::reserved_balance(1), CLAIM_DEPOSIT);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::ClaimCreated(1, claim, CLAIM_DEPOSIT))
+		);
+	})
+}
+
+// 测试创建存证的时候带上元数据，押金按照元数据的长度增加
+#[test]
+fn create_claim_with_metadata_works(){
+	new_test_ext().execute_with(|| {
+		let claim = digest(1);
+		let metadata = ClaimMetadata {
+			title: b"report".to_vec(),
+			content_type: b"application/pdf".to_vec(),
+			size: 1024,
+			uri: Some(b"ipfs://report".to_vec()),
+		};
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim, metadata.clone(), None));
+
+		let proof = Proofs::<Test>::get(&claim).unwrap();
+		assert_eq!(proof.metadata, metadata);
+		// 标题 6 字节 + 文件类型 15 字节 + 链下地址 13 字节及其长度前缀 1 字节
+		assert_eq!(proof.deposit, CLAIM_DEPOSIT + 6 + 15 + 14);
+		assert_eq!(Balances::reserved_balance(1), CLAIM_DEPOSIT + 6 + 15 + 14);
+	})
+}
+
+// 测试元数据太长，创建存证失败的情况，Mock 里边标题、文件类型限制为 16，链下地址限制为 32
+#[test]
+fn create_claim_failed_when_metadata_too_long(){
+	new_test_ext().execute_with(|| {
+		let claim = digest(1);
+		let too_long_title = ClaimMetadata { title: vec![0; 17], ..Default::default() };
+		let too_long_content_type = ClaimMetadata { content_type: vec![0; 17], ..Default::default() };
+		let too_long_uri = ClaimMetadata { uri: Some(vec![0; 33]), ..Default::default() };
+
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(1), claim, too_long_title, None),
+			Error::<Test>::TitleTooLong
+		);
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(1), claim, too_long_content_type, None),
+			Error::<Test>::ContentTypeTooLong
+		);
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(1), claim, too_long_uri, None),
+			Error::<Test>::UriTooLong
+		);
+	})
+}
+
+// 测试存证因为押金不够添加失败的情况
+#[test]
+fn create_claim_failed_when_not_enough_money(){
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(9), digest(1), ClaimMetadata::default(), None),
+			Error::<Test>::MoneyNotEnough
+		);
+	})
+}
+
+// 测试相同哈希、不同算法的摘要是两个不同的存证
+#[test]
+fn create_claim_distinguishes_algorithms(){
+	new_test_ext().execute_with(|| {
+		let blake2 = digest(1);
+		let sha2 = ClaimDigest { algorithm: DigestAlgorithm::Sha2_256, hash: blake2.hash, namespace: None };
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), blake2, ClaimMetadata::default(), None));
+		assert_ok!(PoeModule::create_claim(Origin::signed(2), sha2, ClaimMetadata::default(), None));
+
+		assert_eq!(Proofs::<Test>::get(&sha2).map(|p| p.owner), Some(2));
 	})
 }
 
@@ -17,26 +119,30 @@ fn create_claim_works(){
 #[test]
 fn create_claim_failed_when_claim_already_exist(){
 	new_test_ext().execute_with(|| {
-		let claim = vec![0, 1];
-		let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
 
 		assert_noop!(
-			PoeModule::create_claim(Origin::signed(1), claim.clone()),
+			PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None),
 			Error::<Test>::ProofAlreadyExists
 		);
 	})
 }
 
-// 测试存证因为太长添加失败的情况，Mock 里边将长度设置为了 6
+// 测试通过摘要查询存证，返回拥有者、区块和时间戳
 #[test]
-fn create_claim_failed_when_claim_is_too_long(){
+fn verify_claim_works(){
 	new_test_ext().execute_with(|| {
-		let claim = vec![0, 1, 2, 3, 4, 5, 6];
-		let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+		let claim = digest(1);
+		assert_eq!(PoeModule::verify_claim(&claim), None);
 
-		assert_noop!(
-			PoeModule::create_claim(Origin::signed(1), claim.clone()),
-			Error::<Test>::ProofTooLong
+		System::set_block_number(3);
+		Timestamp::set_timestamp(12_000);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+
+		assert_eq!(
+			PoeModule::verify_claim(&claim),
+			Some(Proof { owner: 1, depositor: 1, block_number: 3, timestamp: 12_000, deposit: CLAIM_DEPOSIT, metadata: ClaimMetadata::default(), expires_at: None })
 		);
 	})
 }
@@ -45,10 +151,17 @@ fn create_claim_failed_when_claim_is_too_long(){
 #[test]
 fn revoke_claim_works(){
 	new_test_ext().execute_with(|| {
-		let claim = vec![0, 1];
-		let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
 
-		assert_ok!( PoeModule::revoke_claim(Origin::signed(1), claim.clone()) );
+		assert_ok!( PoeModule::revoke_claim(Origin::signed(1), claim, None) );
+		assert_eq!(Proofs::<Test>::get(&claim), None);
+		// 押金全部释放
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::ClaimRemoved(1, claim, CLAIM_DEPOSIT))
+		);
 	})
 }
 
@@ -56,10 +169,10 @@ fn revoke_claim_works(){
 #[test]
 fn revoke_claim_failed_when_claim_is_not_exist(){
 	new_test_ext().execute_with(|| {
-		let claim = vec![0, 1];
+		let claim = digest(1);
 
 		assert_noop!(
-			PoeModule::revoke_claim(Origin::signed(1), claim.clone()),
+			PoeModule::revoke_claim(Origin::signed(1), claim, None),
 			Error::<Test>::ProofNotExists
 		);
 	})
@@ -68,11 +181,11 @@ fn revoke_claim_failed_when_claim_is_not_exist(){
 #[test]
 fn revoke_claim_failed_with_wrong_owner(){
 	new_test_ext().execute_with(|| {
-		let claim = vec![0, 1];
-		let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
 
 		assert_noop!(
-			PoeModule::revoke_claim(Origin::signed(2), claim.clone()),
+			PoeModule::revoke_claim(Origin::signed(2), claim, None),
 			Error::<Test>::NotClaimOwner
 		);
 	})
@@ -82,13 +195,35 @@ fn revoke_claim_failed_with_wrong_owner(){
 #[test]
 fn transfer_claim_works(){
 	new_test_ext().execute_with(||{
-		let claim: Vec<u8> = vec![0,1];
-		let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
-		let _ = PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 2);
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::transfer_claim(Origin::signed(1), claim, 2);
+		// 接收人确认之前，存证还是原来的拥有者
+		assert_eq!(Proofs::<Test>::get(&claim).unwrap().owner, 1);
+		assert_ok!(PoeModule::accept_transfer(Origin::signed(2), claim));
+
+		let proof = Proofs::<Test>::get(&claim).unwrap();
+
+		assert_eq!( proof.owner, 2);
+		// 押金从转出人转移到了接收人
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), CLAIM_DEPOSIT);
+	})
+}
+
+// 测试存证转移因为接收人押金不够失败的情况
+#[test]
+fn transfer_claim_failed_when_receiver_not_enough_money(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
 
-		let (owner, _) = Proofs::<Test>::get(&claim);
+		let _ = PoeModule::transfer_claim(Origin::signed(1), claim, 9);
 
-		assert_eq!( owner, 2);
+		assert_noop!(
+			PoeModule::accept_transfer(Origin::signed(9), claim),
+			Error::<Test>::MoneyNotEnough
+		);
 	})
 }
 
@@ -96,9 +231,9 @@ fn transfer_claim_works(){
 #[test]
 fn transfer_claim_failed_when_not_exists(){
 	new_test_ext().execute_with(||{
-		let claim: Vec<u8> = vec![0,1];
+		let claim = digest(1);
 		assert_noop!(
-			PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 2),
+			PoeModule::transfer_claim(Origin::signed(1), claim, 2),
 			Error::<Test>::ProofNotExists
 		);
 	})
@@ -107,12 +242,1492 @@ fn transfer_claim_failed_when_not_exists(){
 #[test]
 fn transfer_claim_failed_when_not_owner(){
 	new_test_ext().execute_with(||{
-		let claim: Vec<u8> = vec![0,1];
-		let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+
+		assert_noop!(
+			PoeModule::transfer_claim(Origin::signed(2), claim, 3),
+			Error::<Test>::NotClaimOwner
+		);
+	})
+}
+
+// 测试修改元数据成功，押金多退少补
+#[test]
+fn set_claim_metadata_works(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+
+		let metadata = ClaimMetadata { title: b"contract".to_vec(), ..Default::default() };
+		assert_ok!(PoeModule::set_claim_metadata(Origin::signed(1), claim, metadata.clone()));
+		assert_eq!(Proofs::<Test>::get(&claim).unwrap().metadata, metadata);
+		assert_eq!(Balances::reserved_balance(1), CLAIM_DEPOSIT + 8);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::ClaimMetadataUpdated(1, claim, CLAIM_DEPOSIT, CLAIM_DEPOSIT + 8))
+		);
+
+		// 清空元数据，多质押的押金会释放
+		assert_ok!(PoeModule::set_claim_metadata(Origin::signed(1), claim, ClaimMetadata::default()));
+		assert_eq!(Balances::reserved_balance(1), CLAIM_DEPOSIT);
+	})
+}
+
+// 测试修改元数据失败，因为不是存证的拥有者
+#[test]
+fn set_claim_metadata_failed_when_not_owner(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+
+		assert_noop!(
+			PoeModule::set_claim_metadata(Origin::signed(2), claim, ClaimMetadata::default()),
+			Error::<Test>::NotClaimOwner
+		);
+	})
+}
+
+// 测试修改元数据失败，因为元数据太长
+#[test]
+fn set_claim_metadata_failed_when_too_long(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+
+		assert_noop!(
+			PoeModule::set_claim_metadata(Origin::signed(1), claim, ClaimMetadata { title: vec![0; 17], ..Default::default() }),
+			Error::<Test>::TitleTooLong
+		);
+	})
+}
+
+// 测试拥有者到存证的反向索引在创建、转移、删除的时候保持一致
+#[test]
+fn owned_claims_follow_create_transfer_revoke(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		assert_eq!(OwnedClaims::<Test>::get(1, &claim), Some(claim));
+		assert_eq!(PoeModule::owned_claims_count(1), 1);
+
+		transfer(1, claim, 2);
+		assert_eq!(OwnedClaims::<Test>::get(1, &claim), None);
+		assert_eq!(OwnedClaims::<Test>::get(2, &claim), Some(claim));
+		assert_eq!(PoeModule::owned_claims_count(1), 0);
+		assert_eq!(PoeModule::owned_claims_count(2), 1);
+
+		let _ = PoeModule::revoke_claim(Origin::signed(2), claim, None);
+		assert_eq!(OwnedClaims::<Test>::get(2, &claim), None);
+		assert_eq!(PoeModule::owned_claims_count(2), 0);
+	})
+}
+
+// 测试拥有的存证数量超过限制的情况，Mock 里边限制为 3
+#[test]
+fn create_claim_failed_when_too_many_claims(){
+	new_test_ext().execute_with(||{
+		for i in 0..3 {
+			assert_ok!(PoeModule::create_claim(Origin::signed(1), digest(i), ClaimMetadata::default(), None));
+		}
+
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(1), digest(3), ClaimMetadata::default(), None),
+			Error::<Test>::TooManyClaims
+		);
+	})
+}
+
+// 测试接收人拥有的存证数量超过限制，转移失败的情况
+#[test]
+fn transfer_claim_failed_when_receiver_has_too_many_claims(){
+	new_test_ext().execute_with(||{
+		for i in 0..3 {
+			let _ = PoeModule::create_claim(Origin::signed(2), digest(i), ClaimMetadata::default(), None);
+		}
+		let _ = PoeModule::create_claim(Origin::signed(1), digest(3), ClaimMetadata::default(), None);
+
+		let _ = PoeModule::transfer_claim(Origin::signed(1), digest(3), 2);
+
+		assert_noop!(
+			PoeModule::accept_transfer(Origin::signed(2), digest(3)),
+			Error::<Test>::TooManyClaims
+		);
+	})
+}
+
+// 测试分页查询某个账号拥有的存证
+#[test]
+fn claims_of_pages_through_owned_claims(){
+	new_test_ext().execute_with(||{
+		for i in 0..3 {
+			let _ = PoeModule::create_claim(Origin::signed(1), digest(i), ClaimMetadata::default(), None);
+		}
+
+		let first = PoeModule::claims_of(&1, 0, 2);
+		let second = PoeModule::claims_of(&1, 1, 2);
+		assert_eq!(first.len(), 2);
+		assert_eq!(second.len(), 1);
+
+		let mut all = [first, second].concat();
+		all.sort_by_key(|claim| claim.hash);
+		assert_eq!(all, vec![digest(0), digest(1), digest(2)]);
+
+		assert!(PoeModule::claims_of(&1, 2, 2).is_empty());
+		assert!(PoeModule::claims_of(&2, 0, 2).is_empty());
+	})
+}
+
+// 测试存证的流转记录：创建者和每一次转让都会追加一条，转让不会丢掉创建的区块
+#[test]
+fn claim_history_records_every_owner(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		Timestamp::set_timestamp(6_000);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+
+		System::set_block_number(2);
+		Timestamp::set_timestamp(12_000);
+		transfer(1, claim, 2);
+
+		System::set_block_number(3);
+		Timestamp::set_timestamp(18_000);
+		transfer(2, claim, 3);
+
+		assert_eq!(
+			PoeModule::claim_history(&claim),
+			vec![
+				ProvenanceRecord { owner: 1, block_number: 1, timestamp: 6_000 },
+				ProvenanceRecord { owner: 2, block_number: 2, timestamp: 12_000 },
+				ProvenanceRecord { owner: 3, block_number: 3, timestamp: 18_000 },
+			]
+		);
+	})
+}
+
+// 测试流转记录满了之后不能再转让，Mock 里边限制为 5 条
+#[test]
+fn transfer_claim_failed_when_history_full(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		for _ in 0..2 {
+			transfer(1, claim, 2);
+			transfer(2, claim, 1);
+		}
+		assert_eq!(PoeModule::claim_history(&claim).len(), 5);
+		let _ = PoeModule::transfer_claim(Origin::signed(1), claim, 2);
 
 		assert_noop!(
-			PoeModule::transfer_claim(Origin::signed(2), claim.clone(), 3),
+			PoeModule::accept_transfer(Origin::signed(2), claim),
+			Error::<Test>::HistoryFull
+		);
+	})
+}
+
+// 测试删除存证的时候流转记录跟着墓碑保留下来，过期的存证没有墓碑，流转记录一起删除
+#[test]
+fn revoke_claim_keeps_history(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::revoke_claim(Origin::signed(1), claim, None);
+
+		assert!(PoeModule::tombstones(&claim).is_some());
+		assert_eq!(PoeModule::claim_history(&claim).len(), 1);
+		assert_eq!(PoeModule::claim_history(&claim)[0].owner, 1);
+
+		let claim = digest(2);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), Some(3));
+		run_to_block(4);
+		assert_eq!(Proofs::<Test>::get(&claim), None);
+		assert!(PoeModule::claim_history(&claim).is_empty());
+	})
+}
+
+// 测试不能把存证转让给自己
+#[test]
+fn transfer_claim_failed_when_transfer_to_self(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+
+		assert_noop!(
+			PoeModule::transfer_claim(Origin::signed(1), claim, 1),
+			Error::<Test>::TransferToSelf
+		);
+	})
+}
+
+// 测试已经有等待确认的转让，不能再发起新的转让
+#[test]
+fn transfer_claim_failed_when_already_pending(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		assert_ok!(PoeModule::transfer_claim(Origin::signed(1), claim, 2));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::ClaimTransferOffered(1, claim, 2, 11))
+		);
+
+		assert_noop!(
+			PoeModule::transfer_claim(Origin::signed(1), claim, 3),
+			Error::<Test>::TransferAlreadyPending
+		);
+	})
+}
+
+// 测试只有接收人能确认或者拒绝转让
+#[test]
+fn accept_transfer_failed_when_not_receiver(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+
+		assert_noop!(
+			PoeModule::accept_transfer(Origin::signed(2), claim),
+			Error::<Test>::NoPendingTransfer
+		);
+
+		let _ = PoeModule::transfer_claim(Origin::signed(1), claim, 2);
+		assert_noop!(
+			PoeModule::accept_transfer(Origin::signed(3), claim),
+			Error::<Test>::NotTransferReceiver
+		);
+		assert_noop!(
+			PoeModule::reject_transfer(Origin::signed(3), claim),
+			Error::<Test>::NotTransferReceiver
+		);
+	})
+}
+
+// 测试接收人拒绝转让
+#[test]
+fn reject_transfer_works(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::transfer_claim(Origin::signed(1), claim, 2);
+
+		assert_ok!(PoeModule::reject_transfer(Origin::signed(2), claim));
+		assert_eq!(PoeModule::pending_transfers(&claim), None);
+		assert_eq!(PoeModule::transfer_expiries(11, &claim), None);
+		assert_eq!(Proofs::<Test>::get(&claim).unwrap().owner, 1);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::ClaimTransferRejected(2, claim))
+		);
+	})
+}
+
+// 测试拥有者取消转让，取消之后接收人不能再确认
+#[test]
+fn cancel_transfer_works(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::transfer_claim(Origin::signed(1), claim, 2);
+
+		assert_noop!(
+			PoeModule::cancel_transfer(Origin::signed(2), claim),
 			Error::<Test>::NotClaimOwner
 		);
+		assert_ok!(PoeModule::cancel_transfer(Origin::signed(1), claim));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::ClaimTransferCancelled(1, claim))
+		);
+		assert_noop!(
+			PoeModule::accept_transfer(Origin::signed(2), claim),
+			Error::<Test>::NoPendingTransfer
+		);
+	})
+}
+
+// 测试等待确认的转让在超时的区块被清理掉，Mock 里边超时时间为 10 个区块
+#[test]
+fn pending_transfer_expires(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::transfer_claim(Origin::signed(1), claim, 2);
+
+		run_to_block(10);
+		assert!(PoeModule::pending_transfers(&claim).is_some());
+
+		run_to_block(11);
+		assert_eq!(PoeModule::pending_transfers(&claim), None);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::ClaimTransferExpired(claim, 2))
+		);
+		assert_noop!(
+			PoeModule::accept_transfer(Origin::signed(2), claim),
+			Error::<Test>::NoPendingTransfer
+		);
+	})
+}
+
+// 测试删除存证的时候，等待确认的转让一起清理
+#[test]
+fn revoke_claim_clears_pending_transfer(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::transfer_claim(Origin::signed(1), claim, 2);
+		let _ = PoeModule::revoke_claim(Origin::signed(1), claim, None);
+
+		assert_eq!(PoeModule::pending_transfers(&claim), None);
+		assert_eq!(PoeModule::transfer_expiries(11, &claim), None);
+	})
+}
+
+// 默克尔树的父节点
+fn merkle_parent(left: [u8; 32], right: [u8; 32]) -> [u8; 32] {
+	sp_io::hashing::blake2_256(&[left, right].concat())
+}
+
+// 测试批量存证成功的情况
+#[test]
+fn anchor_batch_works(){
+	new_test_ext().execute_with(||{
+		let root = [7u8; 32];
+		assert_ok!(PoeModule::anchor_batch(Origin::signed(1), root, 1000));
+
+		assert_eq!(
+			PoeModule::batch_anchors(1, &root),
+			Some(BatchAnchor { owner: 1, leaf_count: 1000, block_number: 1, timestamp: 0, deposit: 10 })
+		);
+		assert_eq!(Balances::reserved_balance(1), 10);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::BatchAnchored(1, root, 1000))
+		);
+	})
+}
+
+// 测试删除批量存证，只能删除自己提交的，删除之后释放押金
+#[test]
+fn remove_batch_works(){
+	new_test_ext().execute_with(||{
+		let root = [7u8; 32];
+		assert_noop!(PoeModule::remove_batch(Origin::signed(1), root), Error::<Test>::BatchNotExists);
+		assert_ok!(PoeModule::anchor_batch(Origin::signed(1), root, 1000));
+
+		assert_noop!(PoeModule::remove_batch(Origin::signed(2), root), Error::<Test>::BatchNotExists);
+		assert_ok!(PoeModule::remove_batch(Origin::signed(1), root));
+		assert_eq!(PoeModule::batch_anchors(1, &root), None);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::BatchRemoved(1, root, 10))
+		);
+	})
+}
+
+// 测试批量存证失败的情况：自己已经提交过这个树根、没有叶子、余额不够
+#[test]
+fn anchor_batch_failed(){
+	new_test_ext().execute_with(||{
+		let root = [7u8; 32];
+		assert_noop!(
+			PoeModule::anchor_batch(Origin::signed(1), root, 0),
+			Error::<Test>::EmptyBatch
+		);
+
+		assert_noop!(
+			PoeModule::anchor_batch(Origin::signed(9), root, 4),
+			Error::<Test>::MoneyNotEnough
+		);
+
+		let _ = PoeModule::anchor_batch(Origin::signed(1), root, 4);
+		assert_noop!(
+			PoeModule::anchor_batch(Origin::signed(1), root, 4),
+			Error::<Test>::BatchAlreadyAnchored
+		);
+	})
+}
+
+// 测试别人抢先提交同一个树根，不影响自己提交，验证的时候按照提交的账号区分
+#[test]
+fn anchor_batch_cannot_be_front_run(){
+	new_test_ext().execute_with(||{
+		let root = [7u8; 32];
+		assert_ok!(PoeModule::anchor_batch(Origin::signed(2), root, 1));
+		assert_ok!(PoeModule::anchor_batch(Origin::signed(1), root, 1));
+
+		assert_eq!(PoeModule::verify_batch_membership(&1, &root, root, 0, &[]).unwrap().owner, 1);
+		assert_eq!(PoeModule::verify_batch_membership(&2, &root, root, 0, &[]).unwrap().owner, 2);
+		assert_eq!(PoeModule::verify_batch_membership(&3, &root, root, 0, &[]), None);
+	})
+}
+
+// 测试用默克尔路径验证单个文件，三个叶子的时候最后一个叶子和自己配对
+#[test]
+fn verify_batch_membership_works(){
+	new_test_ext().execute_with(||{
+		let (a, b, c) = ([1u8; 32], [2u8; 32], [3u8; 32]);
+		let ab = merkle_parent(a, b);
+		let cc = merkle_parent(c, c);
+		let root = merkle_parent(ab, cc);
+		let _ = PoeModule::anchor_batch(Origin::signed(1), root, 3);
+
+		let anchor = PoeModule::batch_anchors(1, &root);
+		assert!(anchor.is_some());
+		assert_eq!(PoeModule::verify_batch_membership(&1, &root, a, 0, &[b, cc]), anchor);
+		assert_eq!(PoeModule::verify_batch_membership(&1, &root, b, 1, &[a, cc]), anchor);
+		assert_eq!(PoeModule::verify_batch_membership(&1, &root, c, 2, &[c, ab]), anchor);
+
+		// 叶子不对、位置不对、路径长度不对、位置超过叶子数量、树根没有上链、不是这个账号提交的
+		assert_eq!(PoeModule::verify_batch_membership(&2, &root, a, 0, &[b, cc]), None);
+		assert_eq!(PoeModule::verify_batch_membership(&1, &root, [9u8; 32], 0, &[b, cc]), None);
+		assert_eq!(PoeModule::verify_batch_membership(&1, &root, a, 1, &[b, cc]), None);
+		assert_eq!(PoeModule::verify_batch_membership(&1, &root, ab, 0, &[cc]), None);
+		assert_eq!(PoeModule::verify_batch_membership(&1, &root, c, 3, &[c, ab]), None);
+		assert_eq!(PoeModule::verify_batch_membership(&1, &[0u8; 32], a, 0, &[b, cc]), None);
+	})
+}
+
+// 测试存证到了过期区块会被自动删除，押金释放
+#[test]
+fn claim_expires_at_given_block(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), Some(5)));
+		assert_eq!(Proofs::<Test>::get(&claim).unwrap().expires_at, Some(5));
+		assert_eq!(PoeModule::claim_expiries(5, &claim), Some(claim));
+
+		run_to_block(4);
+		assert!(Proofs::<Test>::get(&claim).is_some());
+
+		run_to_block(5);
+		assert_eq!(Proofs::<Test>::get(&claim), None);
+		assert_eq!(OwnedClaims::<Test>::get(1, &claim), None);
+		assert_eq!(PoeModule::claim_expiries(5, &claim), None);
+		assert_eq!(PoeModule::claim_expiries_count(5), 0);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			// 过期区块占用 8 个字节，押金也多质押了 8
+			Event::pallet_poe(RawEvent::ClaimExpired(1, claim, CLAIM_DEPOSIT + 8))
+		);
 	})
-}
\ No newline at end of file
+}
+
+// 测试过期区块不能是当前区块或者之前的区块
+#[test]
+fn create_claim_failed_when_expiry_in_past(){
+	new_test_ext().execute_with(||{
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(1), digest(1), ClaimMetadata::default(), Some(1)),
+			Error::<Test>::ExpiryInPast
+		);
+	})
+}
+
+// 测试同一个区块过期的存证数量超过限制，Mock 里边限制为 2
+#[test]
+fn create_claim_failed_when_too_many_expiries(){
+	new_test_ext().execute_with(||{
+		let _ = PoeModule::create_claim(Origin::signed(1), digest(1), ClaimMetadata::default(), Some(5));
+		let _ = PoeModule::create_claim(Origin::signed(1), digest(2), ClaimMetadata::default(), Some(5));
+
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(2), digest(3), ClaimMetadata::default(), Some(5)),
+			Error::<Test>::TooManyExpiries
+		);
+		assert_ok!(PoeModule::create_claim(Origin::signed(2), digest(3), ClaimMetadata::default(), Some(6)));
+	})
+}
+
+// 测试存证延期成功，原来的过期区块不再删除存证
+#[test]
+fn extend_claim_works(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), Some(5));
+
+		assert_ok!(PoeModule::extend_claim(Origin::signed(1), claim, Some(8)));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::ClaimExtended(1, claim, Some(8)))
+		);
+		assert_eq!(PoeModule::claim_expiries(5, &claim), None);
+		assert_eq!(PoeModule::claim_expiries_count(5), 0);
+
+		run_to_block(7);
+		assert!(Proofs::<Test>::get(&claim).is_some());
+		run_to_block(8);
+		assert_eq!(Proofs::<Test>::get(&claim), None);
+	})
+}
+
+// 测试存证延期为永久有效
+#[test]
+fn extend_claim_to_forever_works(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), Some(5));
+
+		assert_eq!(Balances::reserved_balance(1), CLAIM_DEPOSIT + 8);
+
+		assert_ok!(PoeModule::extend_claim(Origin::signed(1), claim, None));
+		// 不再需要保存过期区块，多质押的押金释放
+		assert_eq!(Balances::reserved_balance(1), CLAIM_DEPOSIT);
+		run_to_block(5);
+		assert_eq!(Proofs::<Test>::get(&claim).unwrap().expires_at, None);
+	})
+}
+
+// 测试存证延期失败：不是拥有者、过期区块没有往后调、本来就是永久有效
+#[test]
+fn extend_claim_failed(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), Some(5));
+
+		assert_noop!(
+			PoeModule::extend_claim(Origin::signed(2), claim, Some(8)),
+			Error::<Test>::NotClaimOwner
+		);
+		assert_noop!(
+			PoeModule::extend_claim(Origin::signed(1), claim, Some(5)),
+			Error::<Test>::ExpiryNotExtended
+		);
+
+		let forever = digest(2);
+		let _ = PoeModule::create_claim(Origin::signed(1), forever, ClaimMetadata::default(), None);
+		assert_noop!(
+			PoeModule::extend_claim(Origin::signed(1), forever, Some(8)),
+			Error::<Test>::ExpiryNotExtended
+		);
+	})
+}
+
+// 测试删除存证的时候，过期登记一起清理
+#[test]
+fn revoke_claim_clears_expiry(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), Some(5));
+		let _ = PoeModule::revoke_claim(Origin::signed(1), claim, None);
+
+		assert_eq!(PoeModule::claim_expiries(5, &claim), None);
+		assert_eq!(PoeModule::claim_expiries_count(5), 0);
+	})
+}
+
+// 测试公证人的添加和移除只能由 root 调用
+#[test]
+fn manage_notaries_works(){
+	new_test_ext().execute_with(||{
+		assert_noop!(PoeModule::add_notary(Origin::signed(1), 5), sp_runtime::DispatchError::BadOrigin);
+
+		assert_ok!(PoeModule::add_notary(Origin::root(), 5));
+		assert_eq!(PoeModule::notaries(5), Some(1));
+		assert_noop!(PoeModule::add_notary(Origin::root(), 5), Error::<Test>::AlreadyNotary);
+
+		assert_ok!(PoeModule::remove_notary(Origin::root(), 5));
+		assert_eq!(PoeModule::notaries(5), None);
+		assert_noop!(PoeModule::remove_notary(Origin::root(), 5), Error::<Test>::NotNotary);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::NotaryRemoved(5))
+		);
+	})
+}
+
+// 测试公证人背书和撤回背书
+#[test]
+fn endorse_claim_works(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::add_notary(Origin::root(), 5);
+		let _ = PoeModule::add_notary(Origin::root(), 6);
+
+		assert_ok!(PoeModule::endorse_claim(Origin::signed(5), claim));
+		System::set_block_number(2);
+		assert_ok!(PoeModule::endorse_claim(Origin::signed(6), claim));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::ClaimEndorsed(6, claim))
+		);
+
+		let mut endorsements = PoeModule::endorsements_of(&claim);
+		endorsements.sort();
+		assert_eq!(endorsements, vec![(5, 1), (6, 2)]);
+
+		assert_ok!(PoeModule::withdraw_endorsement(Origin::signed(5), claim));
+		assert_eq!(PoeModule::endorsements_of(&claim), vec![(6, 2)]);
+	})
+}
+
+// 测试背书失败：不是公证人、存证不存在、重复背书、撤回没有做过的背书
+#[test]
+fn endorse_claim_failed(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::add_notary(Origin::root(), 5);
+
+		assert_noop!(PoeModule::endorse_claim(Origin::signed(5), claim), Error::<Test>::ProofNotExists);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		assert_noop!(PoeModule::endorse_claim(Origin::signed(6), claim), Error::<Test>::NotNotary);
+
+		let _ = PoeModule::endorse_claim(Origin::signed(5), claim);
+		assert_noop!(PoeModule::endorse_claim(Origin::signed(5), claim), Error::<Test>::AlreadyEndorsed);
+		assert_noop!(PoeModule::withdraw_endorsement(Origin::signed(6), claim), Error::<Test>::NotEndorsed);
+	})
+}
+
+// 测试背书跟着存证转让，删除存证的时候一起删除
+#[test]
+fn endorsements_follow_claim_lifecycle(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::add_notary(Origin::root(), 5);
+		let _ = PoeModule::endorse_claim(Origin::signed(5), claim);
+
+		transfer(1, claim, 2);
+		assert_eq!(PoeModule::endorsements_of(&claim), vec![(5, 1)]);
+
+		let _ = PoeModule::revoke_claim(Origin::signed(2), claim, None);
+		assert!(PoeModule::endorsements_of(&claim).is_empty());
+		assert_eq!(PoeModule::endorsement_count(&claim), 0);
+	})
+}
+
+// 测试每个存证的背书数量有上限，撤回背书之后可以再背书
+#[test]
+fn endorse_claim_failed_when_too_many(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		for notary in 5..8 {
+			let _ = PoeModule::add_notary(Origin::root(), notary);
+		}
+
+		assert_ok!(PoeModule::endorse_claim(Origin::signed(5), claim));
+		assert_ok!(PoeModule::endorse_claim(Origin::signed(6), claim));
+		assert_eq!(PoeModule::endorsement_count(&claim), 2);
+		assert_noop!(PoeModule::endorse_claim(Origin::signed(7), claim), Error::<Test>::TooManyEndorsements);
+
+		assert_ok!(PoeModule::withdraw_endorsement(Origin::signed(5), claim));
+		assert_eq!(PoeModule::endorsement_count(&claim), 1);
+		assert_ok!(PoeModule::endorse_claim(Origin::signed(7), claim));
+	})
+}
+
+// 测试把存证设置为多人共有，拥有者自动加入共有人名单
+#[test]
+fn set_joint_owners_works(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+
+		assert_ok!(PoeModule::set_joint_owners(Origin::signed(1), claim, vec![3, 2, 3], 2));
+		assert_eq!(
+			PoeModule::joint_owners(&claim),
+			Some(JointOwnership { owners: vec![1, 2, 3], threshold: 2 })
+		);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::JointOwnersSet(1, claim, vec![1, 2, 3], 2))
+		);
+
+		// 多人共有之后，拥有者不能直接删除或者转让
+		assert_noop!(
+			PoeModule::revoke_claim(Origin::signed(1), claim, None),
+			Error::<Test>::JointApprovalRequired
+		);
+		assert_noop!(
+			PoeModule::transfer_claim(Origin::signed(1), claim, 2),
+			Error::<Test>::JointApprovalRequired
+		);
+	})
+}
+
+// 测试有等待接收的转让时不能设置多人共有，否则共有人没办法取消这个转让
+#[test]
+fn set_joint_owners_failed_with_pending_transfer(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), Some(10));
+		assert_ok!(PoeModule::transfer_claim(Origin::signed(1), claim, 3));
+
+		assert_noop!(
+			PoeModule::set_joint_owners(Origin::signed(1), claim, vec![2], 2),
+			Error::<Test>::TransferAlreadyPending
+		);
+		assert_eq!(PoeModule::joint_owners(&claim), None);
+
+		// 转让还是单人拥有的时候发起的，接收之后存证归接收人
+		assert_ok!(PoeModule::accept_transfer(Origin::signed(3), claim));
+		assert_eq!(Proofs::<Test>::get(&claim).unwrap().owner, 3);
+
+		// 多人共有的存证，拥有者也不能一个人延期
+		let claim = digest(2);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), Some(10));
+		let _ = PoeModule::set_joint_owners(Origin::signed(1), claim, vec![2], 2);
+		assert_noop!(
+			PoeModule::extend_claim(Origin::signed(1), claim, Some(20)),
+			Error::<Test>::JointApprovalRequired
+		);
+	})
+}
+
+// 测试设置多人共有失败：不是拥有者、已经是多人共有、同意人数不对、共有人太多（Mock 里边限制为 3）
+#[test]
+fn set_joint_owners_failed(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+
+		assert_noop!(
+			PoeModule::set_joint_owners(Origin::signed(2), claim, vec![2], 1),
+			Error::<Test>::NotClaimOwner
+		);
+		assert_noop!(
+			PoeModule::set_joint_owners(Origin::signed(1), claim, vec![2], 0),
+			Error::<Test>::InvalidThreshold
+		);
+		assert_noop!(
+			PoeModule::set_joint_owners(Origin::signed(1), claim, vec![2], 3),
+			Error::<Test>::InvalidThreshold
+		);
+		assert_noop!(
+			PoeModule::set_joint_owners(Origin::signed(1), claim, vec![2, 3, 4], 2),
+			Error::<Test>::TooManyCoOwners
+		);
+
+		let _ = PoeModule::set_joint_owners(Origin::signed(1), claim, vec![2], 2);
+		assert_noop!(
+			PoeModule::set_joint_owners(Origin::signed(1), claim, vec![3], 2),
+			Error::<Test>::AlreadyJointClaim
+		);
+	})
+}
+
+// 测试共有人同意人数够了之后删除存证
+#[test]
+fn joint_revoke_executes_after_threshold(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::set_joint_owners(Origin::signed(1), claim, vec![2, 3], 2);
+
+		assert_ok!(PoeModule::propose_joint_action(Origin::signed(2), claim, JointAction::Revoke));
+		assert!(Proofs::<Test>::get(&claim).is_some());
+		assert_eq!(
+			PoeModule::joint_proposals(&claim),
+			Some(JointProposal { action: JointAction::Revoke, approvals: vec![2] })
+		);
+
+		assert_noop!(
+			PoeModule::approve_joint_action(Origin::signed(2), claim),
+			Error::<Test>::AlreadyApproved
+		);
+		assert_noop!(
+			PoeModule::approve_joint_action(Origin::signed(4), claim),
+			Error::<Test>::NotCoOwner
+		);
+
+		assert_ok!(PoeModule::approve_joint_action(Origin::signed(3), claim));
+		assert_eq!(Proofs::<Test>::get(&claim), None);
+		assert_eq!(PoeModule::joint_owners(&claim), None);
+		assert_eq!(PoeModule::joint_proposals(&claim), None);
+		// 押金退还给存证的拥有者
+		assert_eq!(Balances::reserved_balance(1), 0);
+	})
+}
+
+// 测试共有人同意转让之后，由接收人确认，转让之后存证变为单人拥有
+#[test]
+fn joint_transfer_executes_after_threshold(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::set_joint_owners(Origin::signed(1), claim, vec![2], 2);
+
+		assert_ok!(PoeModule::propose_joint_action(Origin::signed(1), claim, JointAction::Transfer(3)));
+		assert_eq!(PoeModule::pending_transfers(&claim), None);
+		assert_ok!(PoeModule::approve_joint_action(Origin::signed(2), claim));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::ClaimTransferOffered(1, claim, 3, 11))
+		);
+
+		assert_ok!(PoeModule::accept_transfer(Origin::signed(3), claim));
+		assert_eq!(Proofs::<Test>::get(&claim).unwrap().owner, 3);
+		assert_eq!(PoeModule::joint_owners(&claim), None);
+		assert_ok!(PoeModule::revoke_claim(Origin::signed(3), claim, None));
+	})
+}
+
+// 测试多人共有的存证，拥有者不能一个人取消大家同意的转让、修改元数据或者授权别人
+#[test]
+fn joint_claim_rejects_single_owner_actions(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::set_joint_owners(Origin::signed(1), claim, vec![2], 2);
+		let _ = PoeModule::propose_joint_action(Origin::signed(1), claim, JointAction::Transfer(3));
+		let _ = PoeModule::approve_joint_action(Origin::signed(2), claim);
+
+		assert_noop!(
+			PoeModule::cancel_transfer(Origin::signed(1), claim),
+			Error::<Test>::JointApprovalRequired
+		);
+		assert_noop!(
+			PoeModule::set_claim_metadata(Origin::signed(1), claim, ClaimMetadata::default()),
+			Error::<Test>::JointApprovalRequired
+		);
+		assert_noop!(
+			PoeModule::approve(Origin::signed(1), claim, Some(2)),
+			Error::<Test>::JointApprovalRequired
+		);
+		assert!(PoeModule::pending_transfers(&claim).is_some());
+	})
+}
+
+// 测试取消等待同意的操作，以及不是多人共有的存证不能发起操作
+#[test]
+fn cancel_joint_action_works(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		assert_noop!(
+			PoeModule::propose_joint_action(Origin::signed(1), claim, JointAction::Revoke),
+			Error::<Test>::NotJointClaim
+		);
+
+		let _ = PoeModule::set_joint_owners(Origin::signed(1), claim, vec![2], 2);
+		let _ = PoeModule::propose_joint_action(Origin::signed(1), claim, JointAction::Revoke);
+		assert_noop!(
+			PoeModule::propose_joint_action(Origin::signed(2), claim, JointAction::Transfer(3)),
+			Error::<Test>::ProposalAlreadyPending
+		);
+
+		assert_ok!(PoeModule::cancel_joint_action(Origin::signed(2), claim));
+		assert_eq!(PoeModule::joint_proposals(&claim), None);
+		assert_noop!(
+			PoeModule::approve_joint_action(Origin::signed(2), claim),
+			Error::<Test>::NoPendingProposal
+		);
+	})
+}
+
+// 测试删除存证之后留下墓碑，同样的存证不能再次创建
+#[test]
+fn revoke_claim_leaves_tombstone(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim, Some(7)));
+
+		assert_eq!(
+			PoeModule::tombstones(&claim),
+			Some(Tombstone { revoker: Some(1), block_number: 1, reason: Some(7) })
+		);
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(2), claim, ClaimMetadata::default(), None),
+			Error::<Test>::ProofRevoked
+		);
+	})
+}
+
+// 测试发起异议：质押押金，不能对自己的存证发起，同一时间只能有一个异议
+#[test]
+fn dispute_claim_works(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+
+		assert_noop!(
+			PoeModule::dispute_claim(Origin::signed(1), claim, None),
+			Error::<Test>::CannotDisputeOwnClaim
+		);
+		assert_ok!(PoeModule::dispute_claim(Origin::signed(2), claim, Some(3)));
+		assert_eq!(Balances::reserved_balance(2), 50);
+		assert_eq!(
+			PoeModule::disputes(&claim),
+			Some(Dispute { challenger: 2, block_number: 1, reason: Some(3), deposit: 50 })
+		);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::ClaimDisputed(2, claim, 50))
+		);
+		assert_noop!(
+			PoeModule::dispute_claim(Origin::signed(3), claim, None),
+			Error::<Test>::DisputeAlreadyPending
+		);
+
+		// 有异议的存证，拥有者不能删除、转让或者修改
+		assert_noop!(
+			PoeModule::revoke_claim(Origin::signed(1), claim, None),
+			Error::<Test>::ClaimDisputed
+		);
+		assert_noop!(
+			PoeModule::transfer_claim(Origin::signed(1), claim, 3),
+			Error::<Test>::ClaimDisputed
+		);
+	})
+}
+
+// 测试驳回异议，异议人的押金被罚没
+#[test]
+fn dismiss_dispute_slashes_challenger(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::dispute_claim(Origin::signed(2), claim, None);
+
+		assert_noop!(
+			PoeModule::rule_dispute(Origin::signed(1), claim, DisputeRuling::Dismiss),
+			sp_runtime::traits::BadOrigin
+		);
+		assert_ok!(PoeModule::rule_dispute(Origin::root(), claim, DisputeRuling::Dismiss));
+		assert_eq!(PoeModule::disputes(&claim), None);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::free_balance(2), 950);
+		assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim, None));
+	})
+}
+
+// 测试冻结存证以及解除冻结
+#[test]
+fn freeze_ruling_locks_claim(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::transfer_claim(Origin::signed(1), claim, 3);
+		let _ = PoeModule::dispute_claim(Origin::signed(2), claim, None);
+
+		assert_ok!(PoeModule::rule_dispute(Origin::root(), claim, DisputeRuling::Freeze));
+		assert_eq!(PoeModule::frozen_claims(&claim), Some(1));
+		assert_eq!(PoeModule::pending_transfers(&claim), None);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_noop!(
+			PoeModule::set_claim_metadata(Origin::signed(1), claim, ClaimMetadata::default()),
+			Error::<Test>::ClaimFrozen
+		);
+
+		assert_ok!(PoeModule::unfreeze_claim(Origin::root(), claim));
+		assert_noop!(
+			PoeModule::unfreeze_claim(Origin::root(), claim),
+			Error::<Test>::NotFrozen
+		);
+		assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim, None));
+	})
+}
+
+// 测试把存证判给其他账号
+#[test]
+fn reassign_ruling_transfers_claim(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::dispute_claim(Origin::signed(2), claim, None);
+
+		assert_noop!(
+			PoeModule::rule_dispute(Origin::root(), claim, DisputeRuling::Reassign(1)),
+			Error::<Test>::TransferToSelf
+		);
+		assert_ok!(PoeModule::rule_dispute(Origin::root(), claim, DisputeRuling::Reassign(2)));
+		assert_eq!(Proofs::<Test>::get(&claim).unwrap().owner, 2);
+		// 异议人的押金退还，存证的押金还是由之前的拥有者承担
+		assert_eq!(Balances::reserved_balance(1), CLAIM_DEPOSIT);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Proofs::<Test>::get(&claim).unwrap().depositor, 1);
+		assert_eq!(PoeModule::disputes(&claim), None);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::DisputeRuled(claim, DisputeRuling::Reassign(2)))
+		);
+
+		// 新的拥有者删除存证，押金退还给质押的账号
+		assert_ok!(PoeModule::revoke_claim(Origin::signed(2), claim, None));
+		assert_eq!(Balances::reserved_balance(1), 0);
+	})
+}
+
+// 测试流转记录满了的时候不能判给其他账号，最早的记录不会被改写
+#[test]
+fn reassign_ruling_failed_when_history_full(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		for _ in 0..2 {
+			transfer(1, claim, 2);
+			transfer(2, claim, 1);
+		}
+		let _ = PoeModule::dispute_claim(Origin::signed(2), claim, None);
+
+		assert_noop!(
+			PoeModule::rule_dispute(Origin::root(), claim, DisputeRuling::Reassign(3)),
+			Error::<Test>::HistoryFull
+		);
+		assert_eq!(PoeModule::claim_history(&claim)[0].owner, 1);
+	})
+}
+
+// 测试判给一个没有余额的账号也能执行
+#[test]
+fn reassign_ruling_ignores_receiver_balance(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::dispute_claim(Origin::signed(2), claim, None);
+
+		assert_ok!(PoeModule::rule_dispute(Origin::root(), claim, DisputeRuling::Reassign(9)));
+		assert_eq!(Proofs::<Test>::get(&claim).unwrap().owner, 9);
+		assert_eq!(PoeModule::owned_claims_count(9), 1);
+		assert_eq!(PoeModule::owned_claims_count(1), 0);
+		assert_eq!(PoeModule::claim_history(&claim).last().unwrap().owner, 9);
+	})
+}
+
+// 测试仲裁删除存证，墓碑记录异议的原因，删除人为空
+#[test]
+fn revoke_ruling_leaves_tombstone(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::dispute_claim(Origin::signed(2), claim, Some(9));
+
+		assert_ok!(PoeModule::rule_dispute(Origin::root(), claim, DisputeRuling::Revoke));
+		assert_eq!(Proofs::<Test>::get(&claim), None);
+		assert_eq!(
+			PoeModule::tombstones(&claim),
+			Some(Tombstone { revoker: None, block_number: 1, reason: Some(9) })
+		);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_noop!(
+			PoeModule::rule_dispute(Origin::root(), claim, DisputeRuling::Revoke),
+			Error::<Test>::NoPendingDispute
+		);
+	})
+}
+
+// 拥有者对代为提交的存证签名：前缀 + 创世区块哈希、存证、元数据、过期区块和 nonce
+fn relayed_signature(owner: u64, claim: &ClaimDigest, nonce: u64) -> TestSignature {
+	let mut payload = b"poe/create_claim".to_vec();
+	(System::block_hash(0), claim, ClaimMetadata::default(), None::<u64>, nonce).encode_to(&mut payload);
+	assert_eq!(payload, PoeModule::relayed_claim_payload(claim, &ClaimMetadata::default(), &None, nonce));
+	TestSignature(owner, payload)
+}
+
+// 测试代为提交存证：拥有者是签名的账号，押金由 relayer 质押
+#[test]
+fn create_claim_for_works(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let signature = relayed_signature(9, &claim, 0);
+		assert_ok!(PoeModule::create_claim_for(
+			Origin::signed(2), claim, ClaimMetadata::default(), None, UintAuthorityId(9), 0, signature
+		));
+
+		let proof = Proofs::<Test>::get(&claim).unwrap();
+		assert_eq!(proof.owner, 9);
+		assert_eq!(proof.depositor, 2);
+		assert_eq!(OwnedClaims::<Test>::get(9, &claim), Some(claim));
+		assert_eq!(Balances::reserved_balance(2), CLAIM_DEPOSIT);
+		assert_eq!(PoeModule::nonces(9), 1);
+
+		// 拥有者删除存证，押金退还给 relayer
+		assert_ok!(PoeModule::revoke_claim(Origin::signed(9), claim, None));
+		assert_eq!(Balances::reserved_balance(2), 0);
+	})
+}
+
+// 测试拥有者修改代为提交的存证的元数据，押金从 relayer 转到拥有者身上
+#[test]
+fn set_claim_metadata_moves_relayed_deposit_to_owner(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let signature = relayed_signature(3, &claim, 0);
+		let _ = PoeModule::create_claim_for(
+			Origin::signed(2), claim, ClaimMetadata::default(), None, UintAuthorityId(3), 0, signature
+		);
+		assert_eq!(Balances::reserved_balance(2), CLAIM_DEPOSIT);
+
+		let metadata = ClaimMetadata { title: vec![0; 4], ..Default::default() };
+		assert_ok!(PoeModule::set_claim_metadata(Origin::signed(3), claim, metadata));
+		let proof = Proofs::<Test>::get(&claim).unwrap();
+		assert_eq!(proof.depositor, 3);
+		assert_eq!(proof.deposit, CLAIM_DEPOSIT + 4);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::reserved_balance(3), CLAIM_DEPOSIT + 4);
+	})
+}
+
+// 测试拥有者余额不够的时候不能修改，relayer 的押金保持不变
+#[test]
+fn set_claim_metadata_failed_when_owner_cannot_take_over_deposit(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let signature = relayed_signature(9, &claim, 0);
+		let _ = PoeModule::create_claim_for(
+			Origin::signed(2), claim, ClaimMetadata::default(), None, UintAuthorityId(9), 0, signature
+		);
+
+		assert_noop!(
+			PoeModule::set_claim_metadata(Origin::signed(9), claim, ClaimMetadata::default()),
+			Error::<Test>::MoneyNotEnough
+		);
+		assert_eq!(Balances::reserved_balance(2), CLAIM_DEPOSIT);
+	})
+}
+
+// 测试签名不对或者 nonce 不对的时候失败，用过的签名不能重放
+#[test]
+fn create_claim_for_failed_with_bad_signature_or_nonce(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		assert_noop!(
+			PoeModule::create_claim_for(
+				Origin::signed(2), claim, ClaimMetadata::default(), None, UintAuthorityId(9), 0, relayed_signature(8, &claim, 0)
+			),
+			Error::<Test>::BadSignature
+		);
+		assert_noop!(
+			PoeModule::create_claim_for(
+				Origin::signed(2), claim, ClaimMetadata::default(), Some(5), UintAuthorityId(9), 0, relayed_signature(9, &claim, 0)
+			),
+			Error::<Test>::BadSignature
+		);
+		assert_noop!(
+			PoeModule::create_claim_for(
+				Origin::signed(2), claim, ClaimMetadata::default(), None, UintAuthorityId(9), 1, relayed_signature(9, &claim, 1)
+			),
+			Error::<Test>::InvalidNonce
+		);
+
+		let signature = relayed_signature(9, &claim, 0);
+		assert_ok!(PoeModule::create_claim_for(
+			Origin::signed(2), claim, ClaimMetadata::default(), None, UintAuthorityId(9), 0, signature.clone()
+		));
+		let _ = PoeModule::revoke_claim(Origin::signed(9), claim, None);
+		assert_noop!(
+			PoeModule::create_claim_for(
+				Origin::signed(3), claim, ClaimMetadata::default(), None, UintAuthorityId(9), 0, signature
+			),
+			Error::<Test>::InvalidNonce
+		);
+	})
+}
+
+// 测试 relayer 押金不够的时候失败，nonce 不会增加
+#[test]
+fn create_claim_for_failed_when_relayer_not_enough_money(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		assert_noop!(
+			PoeModule::create_claim_for(
+				Origin::signed(8), claim, ClaimMetadata::default(), None, UintAuthorityId(9), 0, relayed_signature(9, &claim, 0)
+			),
+			Error::<Test>::MoneyNotEnough
+		);
+		assert_eq!(PoeModule::nonces(9), 0);
+	})
+}
+
+// 构造一个命名空间里边的存证摘要
+fn namespaced_digest(n: u8, namespace: NamespaceId) -> ClaimDigest {
+	ClaimDigest { namespace: Some(namespace), ..digest(n) }
+}
+
+// 测试注册命名空间：编号按顺序分配，质押押金
+#[test]
+fn register_namespace_works(){
+	new_test_ext().execute_with(||{
+		assert_ok!(PoeModule::register_namespace(Origin::signed(1)));
+		assert_ok!(PoeModule::register_namespace(Origin::signed(2)));
+
+		assert_eq!(PoeModule::namespaces(0), Some(Namespace { owner: 1, deposit: 100 }));
+		assert_eq!(PoeModule::namespaces(1), Some(Namespace { owner: 2, deposit: 100 }));
+		assert_eq!(PoeModule::next_namespace_id(), 2);
+		assert_eq!(Balances::reserved_balance(1), 100);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::NamespaceRegistered(2, 1, 100))
+		);
+		assert_noop!(
+			PoeModule::register_namespace(Origin::signed(9)),
+			Error::<Test>::MoneyNotEnough
+		);
+	})
+}
+
+// 测试相同的哈希可以同时存在于全局和不同的命名空间里边
+#[test]
+fn same_hash_in_different_namespaces(){
+	new_test_ext().execute_with(||{
+		let _ = PoeModule::register_namespace(Origin::signed(1));
+		let _ = PoeModule::register_namespace(Origin::signed(2));
+
+		assert_ok!(PoeModule::create_claim(Origin::signed(3), digest(1), ClaimMetadata::default(), None));
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), namespaced_digest(1, 0), ClaimMetadata::default(), None));
+		assert_ok!(PoeModule::create_claim(Origin::signed(2), namespaced_digest(1, 1), ClaimMetadata::default(), None));
+
+		assert_eq!(Proofs::<Test>::get(&digest(1)).unwrap().owner, 3);
+		assert_eq!(Proofs::<Test>::get(&namespaced_digest(1, 0)).unwrap().owner, 1);
+		assert_eq!(Proofs::<Test>::get(&namespaced_digest(1, 1)).unwrap().owner, 2);
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(1), namespaced_digest(1, 0), ClaimMetadata::default(), None),
+			Error::<Test>::ProofAlreadyExists
+		);
+	})
+}
+
+// 测试只有命名空间的拥有者和授权的成员可以在命名空间里边创建存证
+#[test]
+fn namespace_writers_work(){
+	new_test_ext().execute_with(||{
+		let _ = PoeModule::register_namespace(Origin::signed(1));
+		let claim = namespaced_digest(1, 0);
+
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(2), namespaced_digest(1, 5), ClaimMetadata::default(), None),
+			Error::<Test>::NamespaceNotExists
+		);
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(2), claim, ClaimMetadata::default(), None),
+			Error::<Test>::NotNamespaceWriter
+		);
+		assert_noop!(
+			PoeModule::add_namespace_writer(Origin::signed(2), 0, 2),
+			Error::<Test>::NotNamespaceOwner
+		);
+
+		assert_ok!(PoeModule::add_namespace_writer(Origin::signed(1), 0, 2));
+		assert_noop!(
+			PoeModule::add_namespace_writer(Origin::signed(1), 0, 2),
+			Error::<Test>::AlreadyNamespaceWriter
+		);
+		assert_ok!(PoeModule::create_claim(Origin::signed(2), claim, ClaimMetadata::default(), None));
+
+		// 取消授权之后不能再创建，之前创建的存证保留
+		assert_ok!(PoeModule::remove_namespace_writer(Origin::signed(1), 0, 2));
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(2), namespaced_digest(2, 0), ClaimMetadata::default(), None),
+			Error::<Test>::NotNamespaceWriter
+		);
+		assert_eq!(Proofs::<Test>::get(&claim).unwrap().owner, 2);
+	})
+}
+
+// 按照 V1_0_0 的存储结构写入一个旧的存证：KEY 是存证的原始内容，值是 (拥有者, 区块)
+fn put_v1_proof(content: &[u8], owner: u64, block_number: u64) {
+	use frame_support::{Blake2_128Concat, StorageHasher, storage::migration::put_storage_value};
+	let key = Blake2_128Concat::hash(&content.to_vec().encode());
+	put_storage_value(b"PoeModule", b"Proofs", &key, (owner, block_number));
+}
+
+// 测试新链的存储版本直接是最新的，升级的时候不会迁移
+#[test]
+fn genesis_storage_version_is_latest(){
+	new_test_ext().execute_with(||{
+		assert_eq!(StorageVersion::get(), Releases::V2_0_0);
+		let _ = PoeModule::create_claim(Origin::signed(1), digest(1), ClaimMetadata::default(), None);
+
+		PoeModule::on_runtime_upgrade();
+		assert_eq!(Proofs::<Test>::get(&digest(1)).unwrap().owner, 1);
+	})
+}
+
+// 测试把 V1_0_0 的存证迁移到新的存储结构
+#[test]
+fn migrate_to_v2_works(){
+	new_test_ext().execute_with(||{
+		StorageVersion::put(Releases::V1_0_0);
+		put_v1_proof(b"hello", 1, 3);
+		put_v1_proof(b"world", 2, 5);
+
+		PoeModule::on_runtime_upgrade();
+		assert_eq!(StorageVersion::get(), Releases::V2_0_0);
+
+		let hello = ClaimDigest { hash: sp_io::hashing::blake2_256(b"hello"), ..digest(0) };
+		let world = ClaimDigest { hash: sp_io::hashing::blake2_256(b"world"), ..digest(0) };
+		assert_eq!(
+			Proofs::<Test>::get(&hello),
+			Some(Proof { owner: 1, depositor: 1, block_number: 3, timestamp: 0, deposit: 0, metadata: ClaimMetadata::default(), expires_at: None })
+		);
+		assert_eq!(Proofs::<Test>::get(&world).unwrap().block_number, 5);
+		assert_eq!(Proofs::<Test>::iter().count(), 2);
+		assert_eq!(OwnedClaims::<Test>::get(1, &hello), Some(hello));
+		assert_eq!(PoeModule::owned_claims_count(2), 1);
+		assert_eq!(PoeModule::claim_history(&hello), vec![ProvenanceRecord { owner: 1, block_number: 3, timestamp: 0 }]);
+
+		// 迁移过来的存证可以正常删除，没有押金需要释放
+		assert_ok!(PoeModule::revoke_claim(Origin::signed(1), hello, None));
+		assert_eq!(Balances::reserved_balance(1), 0);
+	})
+}
+
+// 测试授权的账号可以代为转让存证，拥有者变了之后授权清除
+#[test]
+fn approved_operator_can_transfer_claim(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+
+		assert_noop!(
+			PoeModule::transfer_claim(Origin::signed(2), claim, 3),
+			Error::<Test>::NotClaimOwner
+		);
+		assert_noop!(
+			PoeModule::approve(Origin::signed(2), claim, Some(2)),
+			Error::<Test>::NotClaimOwner
+		);
+		assert_noop!(
+			PoeModule::approve(Origin::signed(1), claim, Some(1)),
+			Error::<Test>::ApprovalToOwner
+		);
+
+		assert_ok!(PoeModule::approve(Origin::signed(1), claim, Some(2)));
+		assert_eq!(PoeModule::approvals(&claim), Some(2));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::ClaimApproved(1, claim, Some(2)))
+		);
+
+		assert_ok!(PoeModule::transfer_claim(Origin::signed(2), claim, 3));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::ClaimTransferOffered(1, claim, 3, 11))
+		);
+		assert_ok!(PoeModule::accept_transfer(Origin::signed(3), claim));
+		assert_eq!(Proofs::<Test>::get(&claim).unwrap().owner, 3);
+		assert_eq!(PoeModule::approvals(&claim), None);
+		assert_noop!(
+			PoeModule::transfer_claim(Origin::signed(2), claim, 1),
+			Error::<Test>::NotClaimOwner
+		);
+	})
+}
+
+// 测试取消单个存证的授权
+#[test]
+fn approve_none_clears_approval(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::approve(Origin::signed(1), claim, Some(2));
+
+		assert_ok!(PoeModule::approve(Origin::signed(1), claim, None));
+		assert_eq!(PoeModule::approvals(&claim), None);
+		assert_noop!(
+			PoeModule::transfer_claim(Origin::signed(2), claim, 3),
+			Error::<Test>::NotClaimOwner
+		);
+	})
+}
+
+// 测试授权账号代为转让拥有者所有的存证，以及取消授权
+#[test]
+fn set_approval_for_all_works(){
+	new_test_ext().execute_with(||{
+		let _ = PoeModule::create_claim(Origin::signed(1), digest(1), ClaimMetadata::default(), None);
+		let _ = PoeModule::create_claim(Origin::signed(1), digest(2), ClaimMetadata::default(), None);
+
+		assert_noop!(
+			PoeModule::set_approval_for_all(Origin::signed(1), 1, true),
+			Error::<Test>::ApprovalToOwner
+		);
+		assert_ok!(PoeModule::set_approval_for_all(Origin::signed(1), 2, true));
+		assert!(PoeModule::operator_approvals(1, 2));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::ApprovalForAll(1, 2, true))
+		);
+
+		assert_ok!(PoeModule::transfer_claim(Origin::signed(2), digest(1), 3));
+		assert_ok!(PoeModule::transfer_claim(Origin::signed(2), digest(2), 3));
+		assert_ok!(PoeModule::cancel_transfer(Origin::signed(2), digest(2)));
+
+		assert_ok!(PoeModule::set_approval_for_all(Origin::signed(1), 2, false));
+		assert!(!PoeModule::operator_approvals(1, 2));
+		assert_noop!(
+			PoeModule::transfer_claim(Origin::signed(2), digest(2), 3),
+			Error::<Test>::NotClaimOwner
+		);
+	})
+}
+
+// 测试挂单出售存证并购买，付款给出售者，押金跟着存证转移
+#[test]
+fn buy_claim_works(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+
+		assert_noop!(
+			PoeModule::ask(Origin::signed(2), claim, Some(100)),
+			Error::<Test>::NotClaimOwner
+		);
+		assert_ok!(PoeModule::ask(Origin::signed(1), claim, Some(100)));
+		assert_eq!(PoeModule::claim_prices(&claim), Some(100));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::ClaimAsk(1, claim, Some(100)))
+		);
+
+		assert_noop!(PoeModule::buy(Origin::signed(1), claim, 100), Error::<Test>::AlreadyOwned);
+		assert_noop!(PoeModule::buy(Origin::signed(2), claim, 99), Error::<Test>::PriceTooLow);
+
+		assert_ok!(PoeModule::buy(Origin::signed(2), claim, 120));
+		assert_eq!(Proofs::<Test>::get(&claim).unwrap().owner, 2);
+		assert_eq!(PoeModule::claim_prices(&claim), None);
+		assert_eq!(Balances::free_balance(1), 1_000 + 100);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::free_balance(2), 1_000 - 100 - CLAIM_DEPOSIT);
+		assert_eq!(Balances::reserved_balance(2), CLAIM_DEPOSIT);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_poe(RawEvent::ClaimSold(1, 2, claim, 100))
+		);
+
+		assert_noop!(PoeModule::buy(Origin::signed(3), claim, 100), Error::<Test>::NotForSale);
+	})
+}
+
+// 测试付款失败的时候，存证和押金都不会转移
+#[test]
+fn buy_claim_is_atomic(){
+	new_test_ext().execute_with(||{
+		let claim = digest(1);
+		let _ = PoeModule::create_claim(Origin::signed(1), claim, ClaimMetadata::default(), None);
+		let _ = PoeModule::ask(Origin::signed(1), claim, Some(950));
+
+		// 账号 2 的余额够质押押金，但是不够付款
+		assert_noop!(PoeModule::buy(Origin::signed(2), claim, 950), Error::<Test>::MoneyNotEnough);
+		assert_eq!(Proofs::<Test>::get(&claim).unwrap().owner, 1);
+		assert_eq!(Balances::reserved_balance(2), 0);
+	})
+}
+
+// 测试取消挂单，以及删除和转让存证的时候挂单一起清除
+#[test]
+fn listing_cleared_on_cancel_transfer_and_revoke(){
+	new_test_ext().execute_with(||{
+		let _ = PoeModule::create_claim(Origin::signed(1), digest(1), ClaimMetadata::default(), None);
+		let _ = PoeModule::create_claim(Origin::signed(1), digest(2), ClaimMetadata::default(), None);
+		let _ = PoeModule::create_claim(Origin::signed(1), digest(3), ClaimMetadata::default(), None);
+		let _ = PoeModule::ask(Origin::signed(1), digest(1), Some(100));
+		let _ = PoeModule::ask(Origin::signed(1), digest(2), Some(100));
+		let _ = PoeModule::ask(Origin::signed(1), digest(3), Some(100));
+
+		assert_ok!(PoeModule::ask(Origin::signed(1), digest(1), None));
+		assert_eq!(PoeModule::claim_prices(&digest(1)), None);
+
+		transfer(1, digest(2), 2);
+		assert_eq!(PoeModule::claim_prices(&digest(2)), None);
+
+		let _ = PoeModule::revoke_claim(Origin::signed(1), digest(3), None);
+		assert_eq!(PoeModule::claim_prices(&digest(3)), None);
+	})
+}
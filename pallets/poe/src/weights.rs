@@ -0,0 +1,132 @@
+//! Weights for pallet_poe
+//!
+//! 下边的数值是按照 benchmarking.rs 估算的初始值，上线之前需要在参考机器上用下边的命令重新生成：
+//!
+//! ./target/release/node-template benchmark \
+//!     --chain=dev --execution=wasm --wasm-execution=compiled \
+//!     --pallet=pallet_poe --extrinsic=* --steps=50 --repeat=20 \
+//!     --output=./pallets/poe/src/weights.rs
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_poe.
+/// 参数 l 是元数据的长度（标题、文件类型、链下地址的字节数之和），e 是存证的背书数量
+pub trait WeightInfo {
+	fn create_claim(l: u32, ) -> Weight;
+	fn create_claim_for(l: u32, ) -> Weight;
+	fn revoke_claim(l: u32, e: u32, ) -> Weight;
+	fn transfer_claim(l: u32, ) -> Weight;
+	fn accept_transfer(l: u32, ) -> Weight;
+	fn set_claim_metadata(l: u32, ) -> Weight;
+	fn anchor_batch() -> Weight;
+	fn remove_batch() -> Weight;
+}
+
+/// Weights for pallet_poe using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	fn create_claim(l: u32, ) -> Weight {
+		(62_310_000 as Weight)
+			.saturating_add((6_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	fn create_claim_for(l: u32, ) -> Weight {
+		(118_640_000 as Weight)
+			.saturating_add((9_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	fn revoke_claim(l: u32, e: u32, ) -> Weight {
+		(71_840_000 as Weight)
+			.saturating_add((3_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add((2_310_000 as Weight).saturating_mul(e as Weight))
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().writes(13 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(e as Weight)))
+	}
+	fn transfer_claim(l: u32, ) -> Weight {
+		(38_920_000 as Weight)
+			.saturating_add((2_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn accept_transfer(l: u32, ) -> Weight {
+		(94_150_000 as Weight)
+			.saturating_add((7_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().writes(12 as Weight))
+	}
+	fn set_claim_metadata(l: u32, ) -> Weight {
+		(47_520_000 as Weight)
+			.saturating_add((5_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn anchor_batch() -> Weight {
+		(45_270_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn remove_batch() -> Weight {
+		(41_630_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn create_claim(l: u32, ) -> Weight {
+		(62_310_000 as Weight)
+			.saturating_add((6_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn create_claim_for(l: u32, ) -> Weight {
+		(118_640_000 as Weight)
+			.saturating_add((9_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
+	fn revoke_claim(l: u32, e: u32, ) -> Weight {
+		(71_840_000 as Weight)
+			.saturating_add((3_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add((2_310_000 as Weight).saturating_mul(e as Weight))
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(13 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(e as Weight)))
+	}
+	fn transfer_claim(l: u32, ) -> Weight {
+		(38_920_000 as Weight)
+			.saturating_add((2_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn accept_transfer(l: u32, ) -> Weight {
+		(94_150_000 as Weight)
+			.saturating_add((7_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(12 as Weight))
+	}
+	fn set_claim_metadata(l: u32, ) -> Weight {
+		(47_520_000 as Weight)
+			.saturating_add((5_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn anchor_batch() -> Weight {
+		(45_270_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn remove_batch() -> Weight {
+		(41_630_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+}
@@ -6,7 +6,6 @@ use pallet_contracts::chain_extension::{
 };
 use sp_runtime::{DispatchError, AccountId32};
 use pallet_contracts::Config;
-use sp_std::vec::Vec;
 use core::convert::TryFrom;
 
 // 转换为 AccountId
@@ -46,9 +45,9 @@ impl<C: Config> ChainExtension<C> for FetchRandomExtension {
 				let input = env.read(1)?; // Read n incoming parameters
 				debug::info!("input with len: {}", input[0]);
                 
-				let input: Vec<u8> = env.read_as()?;
+				let input: pallet_poe::ClaimDigest = env.read_as()?;
 				let who = to_account_id(caller.as_ref());
-				super::PoeModule::do_create_claim(who, input)?;
+				super::PoeModule::do_create_claim(who.clone(), who, input, Default::default(), None)?;
             }
             1103 => { // 调用 Kitties 模块，创建一个 Kitty
                 debug::info!("run 1103");
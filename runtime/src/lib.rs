@@ -107,10 +107,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("node-template"),
 	impl_name: create_runtime_str!("node-template"),
 	authoring_version: 1,
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 };
 
 
@@ -335,15 +335,53 @@ impl pallet_template::Config for Runtime {
 }
 
 parameter_types! {
-	pub const MaxClaimLength: u32 = 6;
+	pub const ClaimDepositBase: Balance = deposit(1, 0);
+	pub const ClaimDepositPerByte: Balance = deposit(0, 1);
+	pub const MaxTitleLength: u32 = 128;
+	pub const MaxContentTypeLength: u32 = 64;
+	pub const MaxUriLength: u32 = 256;
+	pub const MaxClaimsPerAccount: u32 = 1_000;
+	pub const MaxHistoryLength: u32 = 100;
+	pub const TransferOfferTimeout: BlockNumber = DAYS;
+	pub const MaxExpiriesPerBlock: u32 = 100;
+	pub const MaxCoOwners: u32 = 16;
+	pub const MaxEndorsementsPerClaim: u32 = 32;
+	pub const DisputeDeposit: Balance = 10 * DOLLARS;
+	pub const NamespaceDeposit: Balance = 10 * DOLLARS;
 }
+
 impl pallet_poe::Config for Runtime {
 	type Event = Event;
-	type MaxClaimLength = MaxClaimLength;
+	type Time = Timestamp;
+	type Currency = Balances;
+	type ClaimDepositBase = ClaimDepositBase;
+	type ClaimDepositPerByte = ClaimDepositPerByte;
+	type MaxTitleLength = MaxTitleLength;
+	type MaxContentTypeLength = MaxContentTypeLength;
+	type MaxUriLength = MaxUriLength;
+	type MaxClaimsPerAccount = MaxClaimsPerAccount;
+	type MaxHistoryLength = MaxHistoryLength;
+	type TransferOfferTimeout = TransferOfferTimeout;
+	type MaxExpiriesPerBlock = MaxExpiriesPerBlock;
+	type NotaryOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxCoOwners = MaxCoOwners;
+	type MaxEndorsementsPerClaim = MaxEndorsementsPerClaim;
+	type DisputeOrigin = frame_system::EnsureRoot<AccountId>;
+	type DisputeDeposit = DisputeDeposit;
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+	type NamespaceDeposit = NamespaceDeposit;
+	type WeightInfo = pallet_poe::weights::SubstrateWeight<Runtime>;
 }
 
 parameter_types! {
 	pub const NewKittyReserve: u64 = 5_000;
+	pub const MaxAuctionDuration: BlockNumber = 7 * DAYS;
+	pub const MaxAuctionsPerBlock: u32 = 50;
+	pub const BaseBreedingCooldown: BlockNumber = 10 * MINUTES;
+	pub const MaxLitters: u32 = 10;
+	pub const MaxOffersPerKitty: u32 = 20;
+	pub const MaxOfferDuration: BlockNumber = 7 * DAYS;
 }
 
 impl pallet_kitties::Config for Runtime {
@@ -352,6 +390,13 @@ impl pallet_kitties::Config for Runtime {
 	type NewKittyReserve = NewKittyReserve;
 	type KittyIndex = u32;
 	type Currency = Balances;
+	type MaxAuctionDuration = MaxAuctionDuration;
+	type MaxAuctionsPerBlock = MaxAuctionsPerBlock;
+	type DepositOrigin = frame_system::EnsureRoot<AccountId>;
+	type BaseBreedingCooldown = BaseBreedingCooldown;
+	type MaxLitters = MaxLitters;
+	type MaxOffersPerKitty = MaxOffersPerKitty;
+	type MaxOfferDuration = MaxOfferDuration;
 }
 
 impl pallet_dotprices::Trait for Runtime {
@@ -439,8 +484,8 @@ construct_runtime!(
 		Contracts: pallet_contracts::{Module, Call, Config<T>, Storage, Event<T>},
 		// Include the custom logic from the template pallet in the runtime.
 		TemplateModule: pallet_template::{Module, Call, Storage, Event<T>},
-		PoeModule: pallet_poe::{Module, Call, Storage, Event<T>},
-		KittiesModule: pallet_kitties::{Module, Call, Storage, Event<T>},
+		PoeModule: pallet_poe::{Module, Call, Config, Storage, Event<T>},
+		KittiesModule: pallet_kitties::{Module, Call, Config, Storage, Event<T>},
 		DotpricesModule: pallet_dotprices::{Module, Call, Storage, Event<T>},
 	}
 );
@@ -612,6 +657,52 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_poe_runtime_api::PoeApi<Block, AccountId, BlockNumber, u64, Balance> for Runtime {
+		fn verify_claim(
+			claim: pallet_poe::ClaimDigest,
+		) -> Option<pallet_poe::Proof<AccountId, BlockNumber, u64, Balance>> {
+			PoeModule::verify_claim(&claim)
+		}
+
+		fn claims_of(owner: AccountId, page: u32, page_size: u32) -> Vec<pallet_poe::ClaimDigest> {
+			PoeModule::claims_of(&owner, page, page_size)
+		}
+
+		fn claim_history(
+			claim: pallet_poe::ClaimDigest,
+		) -> Vec<pallet_poe::ProvenanceRecord<AccountId, BlockNumber, u64>> {
+			PoeModule::claim_history(&claim)
+		}
+
+		fn endorsements_of(claim: pallet_poe::ClaimDigest) -> Vec<(AccountId, BlockNumber)> {
+			PoeModule::endorsements_of(&claim)
+		}
+
+		fn verify_batch_membership(
+			owner: AccountId,
+			root: [u8; 32],
+			leaf: [u8; 32],
+			index: u32,
+			path: Vec<[u8; 32]>,
+		) -> Option<pallet_poe::BatchAnchor<AccountId, BlockNumber, u64, Balance>> {
+			PoeModule::verify_batch_membership(&owner, &root, leaf, index, &path)
+		}
+
+		fn tombstone_of(claim: pallet_poe::ClaimDigest) -> Option<pallet_poe::Tombstone<AccountId, BlockNumber>> {
+			PoeModule::tombstones(&claim)
+		}
+	}
+
+	impl pallet_kitties_runtime_api::KittiesApi<Block, AccountId, u32> for Runtime {
+		fn check_invariants() -> Vec<pallet_kitties::InvariantViolation<AccountId, u32>> {
+			KittiesModule::check_invariants()
+		}
+
+		fn kitty_info(kitty_id: u32) -> Option<pallet_kitties::KittyInfo> {
+			KittiesModule::kitty_info(kitty_id)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(
@@ -648,6 +739,7 @@ impl_runtime_apis! {
 			add_benchmark!(params, batches, pallet_timestamp, Timestamp);
 			add_benchmark!(params, batches, pallet_template, TemplateModule);
 			add_benchmark!(params, batches, pallet_kitties, KittiesModule);
+			add_benchmark!(params, batches, pallet_poe, PoeModule);
 
 			if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
 			Ok(batches)